@@ -1,7 +1,7 @@
 use serde::Serialize;
 
 use tracel_experiment::ExperimentJob;
-use tracel_inference::InferenceJob;
+use tracel_inference::{InferenceJob, LatencyReport, ProfileSettings};
 
 use crate::cli::error::CliError;
 use crate::cli::mapper::Mapper;
@@ -18,6 +18,19 @@ pub trait CliCommand: Send + Sync {
     fn name(&self) -> &str;
     /// Run the command with the given raw config string.
     fn run(&self, config: &str) -> Result<(), CliError>;
+    /// Measure the command's request latency with the given raw config string.
+    ///
+    /// Only inference commands support profiling; the default returns
+    /// [`CliError::ProfilingUnsupported`].
+    fn profile(
+        &self,
+        _config: &str,
+        _settings: &ProfileSettings,
+    ) -> Result<LatencyReport, CliError> {
+        Err(CliError::ProfilingUnsupported {
+            name: self.name().to_string(),
+        })
+    }
 }
 
 /// Turns a capability job plus a config mapper into a [`CliCommand`].
@@ -101,6 +114,24 @@ where
         }
         Ok(())
     }
+
+    /// Profile with the config decoded on every iteration and outputs serialized to JSON, as
+    /// [`run`](Self::run) does, but without printing them.
+    fn profile(&self, config: &str, settings: &ProfileSettings) -> Result<LatencyReport, CliError> {
+        self.mapper
+            .map(config)
+            .map_err(CliError::ValidationFailed)?;
+        self.job
+            .profile(
+                settings,
+                || self.mapper.map(config),
+                |output| {
+                    serde_json::to_string(&output)?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| CliError::ExecutionFailed(Box::new(e)))
+    }
 }
 
 impl<I, O, M> IntoCliCommand<M> for InferenceJob<I, O>
@@ -1,5 +1,7 @@
 use std::error::Error;
 
+use crate::version::SdkVersionMismatch;
+
 #[derive(Debug, thiserror::Error)]
 pub enum CliError {
     #[error("no command name given and no default registered")]
@@ -11,9 +13,27 @@ pub enum CliError {
         available: Vec<String>,
     },
 
+    #[error("command '{name}' does not support profiling")]
+    ProfilingUnsupported { name: String },
+
     #[error("validation failed: {0}")]
     ValidationFailed(#[source] Box<dyn Error + Send + Sync>),
 
+    #[error(
+        "project was packaged for tracel {packaged} but this runner uses tracel {runtime}; \
+         re-upload the project with a CLI compatible with tracel {runtime}"
+    )]
+    IncompatibleSdkVersion { packaged: String, runtime: String },
+
     #[error("execution failed: {0}")]
     ExecutionFailed(#[source] Box<dyn Error + Send + Sync>),
 }
+
+impl From<SdkVersionMismatch> for CliError {
+    fn from(mismatch: SdkVersionMismatch) -> Self {
+        CliError::IncompatibleSdkVersion {
+            packaged: mismatch.packaged,
+            runtime: mismatch.runtime,
+        }
+    }
+}
@@ -9,13 +9,26 @@ pub use error::CliError;
 use clap::Parser;
 use std::collections::HashMap;
 use std::error::Error;
+use std::num::NonZeroUsize;
 use tracel_experiment::ExperimentJob;
+use tracel_inference::{LatencyReport, ProfileSettings};
+
+use crate::version;
 
 #[derive(Parser)]
 #[command(about = "Run a registered command")]
 struct Args {
     command: Option<String>,
     config: Option<String>,
+    /// Print per-stage latency of the inference command instead of its outputs.
+    #[arg(long)]
+    profile: bool,
+    /// Iterations run before measuring when profiling.
+    #[arg(long, requires = "profile")]
+    warmup: Option<usize>,
+    /// Iterations measured when profiling, at least one.
+    #[arg(long, requires = "profile")]
+    iterations: Option<NonZeroUsize>,
 }
 
 struct DefaultCommand {
@@ -75,21 +88,31 @@ impl Cli {
 
     pub fn run(self) -> Result<(), CliError> {
         let args = Args::parse();
-        self.dispatch(args.command, args.config)
+        version::check_sdk_version(version::packaged_sdk_version().as_deref())?;
+        if !args.profile {
+            return self.dispatch(args.command, args.config);
+        }
+
+        let defaults = ProfileSettings::default();
+        let settings = ProfileSettings {
+            warmup: args.warmup.unwrap_or(defaults.warmup),
+            iterations: args
+                .iterations
+                .map_or(defaults.iterations, NonZeroUsize::get),
+        };
+        let report = self.profile(args.command, args.config, &settings)?;
+        eprint!("{report}");
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| CliError::ExecutionFailed(Box::new(e)))?;
+        println!("{json}");
+        Ok(())
     }
 
     fn dispatch(self, command: Option<String>, config: Option<String>) -> Result<(), CliError> {
         match command {
             Some(name) => {
                 let config_str = config.unwrap_or_default();
-                let command = self
-                    .commands
-                    .get(&name)
-                    .ok_or_else(|| CliError::UnknownCommand {
-                        name: name.clone(),
-                        available: self.commands.keys().cloned().collect(),
-                    })?;
-                command.run(&config_str)
+                self.command_named(&name)?.run(&config_str)
             }
             None => {
                 let d = self.default.ok_or(CliError::MissingDefault)?;
@@ -97,6 +120,30 @@ impl Cli {
             }
         }
     }
+
+    /// Profile the named command. The default job is an experiment and cannot be profiled.
+    fn profile(
+        &self,
+        command: Option<String>,
+        config: Option<String>,
+        settings: &ProfileSettings,
+    ) -> Result<LatencyReport, CliError> {
+        let name = command.ok_or_else(|| CliError::ProfilingUnsupported {
+            name: "default".to_string(),
+        })?;
+        self.command_named(&name)?
+            .profile(&config.unwrap_or_default(), settings)
+    }
+
+    fn command_named(&self, name: &str) -> Result<&dyn CliCommand, CliError> {
+        self.commands
+            .get(name)
+            .map(|command| command.as_ref())
+            .ok_or_else(|| CliError::UnknownCommand {
+                name: name.to_string(),
+                available: self.commands.keys().cloned().collect(),
+            })
+    }
 }
 
 #[cfg(test)]
@@ -176,6 +223,34 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn given_profiling_flags_when_parsing_then_they_require_profile() {
+        use clap::CommandFactory;
+        Args::command().debug_assert();
+
+        let args =
+            Args::try_parse_from(["app", "infer", "{}", "--profile", "--iterations", "5"]).unwrap();
+        assert!(args.profile);
+        assert_eq!(args.iterations, NonZeroUsize::new(5));
+        assert!(Args::try_parse_from(["app", "infer", "--warmup", "1"]).is_err());
+    }
+
+    #[test]
+    fn given_zero_iterations_when_parsing_then_fails() {
+        let args = Args::try_parse_from(["app", "infer", "--profile", "--iterations", "0"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn given_command_without_profiling_when_profiling_then_returns_unsupported() {
+        let cli = Cli::new().command(FakeCommand::new("train"));
+        let result = cli.profile(Some("train".into()), None, &ProfileSettings::default());
+        assert!(matches!(
+            result,
+            Err(CliError::ProfilingUnsupported { name }) if name == "train"
+        ));
+    }
+
     #[test]
     #[should_panic(expected = "already registered")]
     fn given_duplicate_command_name_when_registering_then_panics() {
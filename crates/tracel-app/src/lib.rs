@@ -14,3 +14,5 @@ pub mod cli;
 /// HTTP server front-end.
 #[cfg(feature = "server")]
 pub mod server;
+
+mod version;
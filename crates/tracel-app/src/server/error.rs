@@ -1,5 +1,22 @@
+use crate::version::SdkVersionMismatch;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ServerError {
     #[error("server error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error(
+        "project was packaged for tracel {packaged} but this server uses tracel {runtime}; \
+         re-upload the project with a CLI compatible with tracel {runtime}"
+    )]
+    IncompatibleSdkVersion { packaged: String, runtime: String },
+}
+
+impl From<SdkVersionMismatch> for ServerError {
+    fn from(mismatch: SdkVersionMismatch) -> Self {
+        ServerError::IncompatibleSdkVersion {
+            packaged: mismatch.packaged,
+            runtime: mismatch.runtime,
+        }
+    }
 }
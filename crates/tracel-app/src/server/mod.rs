@@ -19,6 +19,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::version;
+
 type Routes = HashMap<String, Box<dyn ServerRoute>>;
 
 pub struct Server {
@@ -82,7 +84,11 @@ impl Server {
         self.route_boxed(job.into_server_route(Arc::new(mapper)))
     }
 
+    /// Serve the registered routes until the server stops.
+    ///
+    /// Fails before binding if the project was packaged for an incompatible SDK version.
     pub async fn run_async(self) -> Result<(), ServerError> {
+        version::check_sdk_version(version::packaged_sdk_version().as_deref())?;
         let addr = format!("{}:{}", self.host, self.port);
         let state = Arc::new(self.routes);
 
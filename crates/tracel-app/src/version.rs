@@ -0,0 +1,106 @@
+//! Startup check that the project was packaged for the SDK version the runner built.
+
+/// Environment variable holding the SDK version recorded when the project was packaged.
+pub(crate) const PACKAGED_SDK_VERSION: &str = "TRACEL_PACKAGED_SDK_VERSION";
+
+/// SDK version this binary was built against.
+pub(crate) const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The packaged SDK version is not compatible with the SDK version linked in.
+#[derive(Debug)]
+pub(crate) struct SdkVersionMismatch {
+    pub(crate) packaged: String,
+    pub(crate) runtime: String,
+}
+
+/// The SDK version the project was packaged for, if known.
+///
+/// It is embedded at build time when [`PACKAGED_SDK_VERSION`] is set while building the project,
+/// as the packaging step does. The embedded version always wins, so a stray variable in the
+/// runner's environment cannot mask a mismatch; the variable is only read at run time when
+/// nothing was embedded. When neither is set the check is skipped.
+pub(crate) fn packaged_sdk_version() -> Option<String> {
+    resolve_packaged_sdk_version(option_env!("TRACEL_PACKAGED_SDK_VERSION"), || {
+        std::env::var(PACKAGED_SDK_VERSION).ok()
+    })
+}
+
+fn resolve_packaged_sdk_version(
+    embedded: Option<&str>,
+    runtime: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    embedded.map(str::to_string).or_else(runtime)
+}
+
+/// Fail early when the packaged SDK version is not compatible with the one linked in.
+///
+/// Versions are compatible under Cargo's semver rules: same major version, or the same minor
+/// version while the major version is `0`.
+pub(crate) fn check_sdk_version(packaged: Option<&str>) -> Result<(), SdkVersionMismatch> {
+    let Some(packaged) = packaged else {
+        return Ok(());
+    };
+
+    if compatibility_key(packaged).is_some_and(|key| Some(key) == compatibility_key(SDK_VERSION)) {
+        return Ok(());
+    }
+
+    Err(SdkVersionMismatch {
+        packaged: packaged.to_string(),
+        runtime: SDK_VERSION.to_string(),
+    })
+}
+
+/// The part of a version that must match for two versions to be compatible.
+fn compatibility_key(version: &str) -> Option<(u64, Option<u64>)> {
+    let mut parts = version.trim().split(['.', '-', '+']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some(if major == 0 {
+        (0, Some(minor))
+    } else {
+        (major, None)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_no_packaged_version_when_checking_then_passes() {
+        assert!(check_sdk_version(None).is_ok());
+    }
+
+    #[test]
+    fn given_same_version_when_checking_then_passes() {
+        assert!(check_sdk_version(Some(SDK_VERSION)).is_ok());
+    }
+
+    #[test]
+    fn given_mismatched_version_when_checking_then_returns_incompatible_error() {
+        let mismatch = check_sdk_version(Some("999.0.0")).unwrap_err();
+        assert_eq!(mismatch.packaged, "999.0.0");
+        assert_eq!(mismatch.runtime, SDK_VERSION);
+    }
+
+    #[test]
+    fn given_embedded_version_when_resolving_then_ignores_the_runtime_variable() {
+        assert_eq!(
+            resolve_packaged_sdk_version(Some("0.7.0"), || Some("0.8.0".to_string())),
+            Some("0.7.0".to_string())
+        );
+        assert_eq!(
+            resolve_packaged_sdk_version(None, || Some("0.8.0".to_string())),
+            Some("0.8.0".to_string())
+        );
+        assert_eq!(resolve_packaged_sdk_version(None, || None), None);
+    }
+
+    #[test]
+    fn given_pre_1_0_versions_when_comparing_then_minor_must_match() {
+        assert_eq!(compatibility_key("0.7.3"), Some((0, Some(7))));
+        assert_eq!(compatibility_key("1.2.0-rc.1"), Some((1, None)));
+        assert_eq!(compatibility_key("garbage"), None);
+    }
+}
@@ -1,9 +1,19 @@
 //! This module provides utilities for downloading artifact files from any source to any target bundle sink.
 //!
 //! Downloaded files are validated against expected sizes and checksums when provided, and the download process can be customized with any implementation of the FileTransferClient trait (e.g. for custom HTTP clients, authentication, retries, etc).
+//!
+//! A download interrupted by a read error is resumed from the last byte received, with
+//! [`FileTransferClient::get_reader_from`], up to [`MAX_RESUMES`] times per file.
+//!
+//! [`download_artifacts_to_sink_resumable`] also keeps each file in a partial file until it is
+//! complete, next to a small state file recording how many bytes were written and the server's
+//! entity tag. A download that failed, even in another process, continues from there with
+//! [`FileTransferClient::get_partial`]; if the file changed on the server, it restarts.
 
 use std::collections::HashSet;
-use std::io::Read;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
 use sha2::Digest;
 
@@ -12,6 +22,12 @@ use crate::tools::path::normalize_bundle_path;
 use crate::tools::validation::normalize_checksum;
 use crate::{FileTransferClient, ReqwestTransferClient};
 
+/// How many times one file download is resumed after a read error before giving up.
+pub const MAX_RESUMES: usize = 3;
+
+/// Bytes written to a partial file between two saves of its state.
+const PARTIAL_STATE_INTERVAL: u64 = 8 * 1024 * 1024;
+
 /// Errors that can occur during artifact file downloads.
 #[derive(Debug, thiserror::Error)]
 pub enum DownloadError {
@@ -81,6 +97,13 @@ pub fn download_artifacts_to_sink_with_client<FTC: FileTransferClient, S: Bundle
                 rel_path: rel_path.clone(),
                 source: e,
             })?;
+        let reader = ResumingReader {
+            client,
+            url: &file.url,
+            inner: reader,
+            offset: 0,
+            resumes_left: MAX_RESUMES,
+        };
         let mut verifying_reader = VerifyingReader::new(reader);
 
         sink.put_file(&rel_path, &mut verifying_reader)
@@ -99,6 +122,195 @@ pub fn download_artifacts_to_sink_with_client<FTC: FileTransferClient, S: Bundle
     Ok(())
 }
 
+/// Download artifact files into any bundle sink, keeping partial downloads in `partial_dir`.
+///
+/// A file whose download fails stays in `partial_dir`, and the next call for the same file
+/// continues from where it stopped. Partial files are removed once their file is complete and
+/// verified. Resuming across calls requires the server to report an entity tag or the file to
+/// have an expected checksum; otherwise the download starts over.
+pub fn download_artifacts_to_sink_resumable<FTC: FileTransferClient, S: BundleSink>(
+    client: &FTC,
+    sink: &mut S,
+    files: &[ArtifactDownloadFile],
+    partial_dir: &Path,
+) -> Result<(), DownloadError> {
+    let target_error = |e: std::io::Error| DownloadError::TargetError(e.to_string());
+    fs::create_dir_all(partial_dir).map_err(target_error)?;
+
+    let files = validated_download_files(files)?;
+    for (rel_path, file) in files {
+        let partial = PartialFile::new(partial_dir, &rel_path, file);
+        partial.download(client, &rel_path, file)?;
+
+        let mut verifying_reader =
+            VerifyingReader::new(File::open(&partial.path).map_err(target_error)?);
+        sink.put_file(&rel_path, &mut verifying_reader)
+            .map_err(DownloadError::TargetError)?;
+
+        let (total, digest) = verifying_reader.finish();
+        let validated = validate_download(
+            &rel_path,
+            total,
+            digest,
+            file.size_bytes,
+            file.checksum.as_deref(),
+        );
+        // A partial file that fails validation is corrupt, so it is not resumed either.
+        partial.remove();
+        validated?;
+    }
+
+    Ok(())
+}
+
+/// A file being downloaded into `partial_dir`, with its state file.
+struct PartialFile {
+    path: PathBuf,
+    state_path: PathBuf,
+}
+
+/// Progress of a partial file, saved as `offset=<bytes>` and `etag=<entity tag>` lines.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PartialState {
+    offset: u64,
+    etag: Option<String>,
+}
+
+impl PartialState {
+    fn parse(contents: &str) -> Option<Self> {
+        let mut state = PartialState::default();
+        for line in contents.lines() {
+            match line.split_once('=')? {
+                ("offset", offset) => state.offset = offset.parse().ok()?,
+                ("etag", etag) if !etag.is_empty() => state.etag = Some(etag.to_string()),
+                _ => {}
+            }
+        }
+        Some(state)
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "offset={}\netag={}\n",
+            self.offset,
+            self.etag.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+impl PartialFile {
+    /// Partial files are named after the file's path and expected content, since presigned URLs
+    /// change between requests.
+    fn new(partial_dir: &Path, rel_path: &str, file: &ArtifactDownloadFile) -> Self {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(rel_path.as_bytes());
+        hasher.update([0]);
+        hasher.update(file.checksum.as_deref().unwrap_or_default().as_bytes());
+        hasher.update(file.size_bytes.unwrap_or_default().to_le_bytes());
+        let name = format!("{:x}", hasher.finalize());
+
+        Self {
+            path: partial_dir.join(format!("{name}.part")),
+            state_path: partial_dir.join(format!("{name}.part.state")),
+        }
+    }
+
+    /// State to resume from, if the partial file can be resumed.
+    ///
+    /// Without an entity tag or an expected checksum, a changed file on the server could not be
+    /// detected, so the download starts over.
+    fn resumable_state(&self, file: &ArtifactDownloadFile) -> PartialState {
+        let state = fs::read_to_string(&self.state_path)
+            .ok()
+            .and_then(|contents| PartialState::parse(&contents))
+            .unwrap_or_default();
+        let written = fs::metadata(&self.path).map_or(0, |metadata| metadata.len());
+        if state.offset > written || (state.etag.is_none() && file.checksum.is_none()) {
+            return PartialState::default();
+        }
+        state
+    }
+
+    fn save(&self, state: &PartialState) -> std::io::Result<()> {
+        fs::write(&self.state_path, state.serialize())
+    }
+
+    /// Download the rest of `file` into the partial file.
+    fn download<FTC: FileTransferClient>(
+        &self,
+        client: &FTC,
+        rel_path: &str,
+        file: &ArtifactDownloadFile,
+    ) -> Result<(), DownloadError> {
+        let target_error = |e: std::io::Error| DownloadError::TargetError(e.to_string());
+        let state = self.resumable_state(file);
+        if state.offset > 0 {
+            tracing::debug!(
+                file = rel_path,
+                offset = state.offset,
+                "Resuming partial download"
+            );
+        }
+
+        let download = client
+            .get_partial(&file.url, state.offset, state.etag.as_deref())
+            .map_err(|e| DownloadError::Transfer {
+                rel_path: rel_path.to_string(),
+                source: e,
+            })?;
+        let mut state = PartialState {
+            offset: download.offset,
+            etag: download.etag,
+        };
+        let mut out = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.path)
+            .map_err(target_error)?;
+        // Bytes written after the last saved state may be incomplete, so they are downloaded again.
+        out.set_len(state.offset).map_err(target_error)?;
+        out.seek(SeekFrom::End(0)).map_err(target_error)?;
+        self.save(&state).map_err(target_error)?;
+
+        let mut reader = ResumingReader {
+            client,
+            url: &file.url,
+            inner: download.reader,
+            offset: state.offset,
+            resumes_left: MAX_RESUMES,
+        };
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut saved = state.offset;
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) => {
+                    self.save(&state).map_err(target_error)?;
+                    return Err(DownloadError::Transfer {
+                        rel_path: rel_path.to_string(),
+                        source: crate::transfer::TransferError::Transport(err.to_string()),
+                    });
+                }
+            };
+            out.write_all(&buf[..read]).map_err(target_error)?;
+            state.offset += read as u64;
+            if state.offset - saved >= PARTIAL_STATE_INTERVAL {
+                self.save(&state).map_err(target_error)?;
+                saved = state.offset;
+            }
+        }
+
+        self.save(&state).map_err(target_error)
+    }
+
+    fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_file(&self.state_path);
+    }
+}
+
 fn validated_download_files(
     files: &[ArtifactDownloadFile],
 ) -> Result<Vec<(String, &ArtifactDownloadFile)>, DownloadError> {
@@ -123,6 +335,45 @@ fn validated_download_files(
     Ok(out)
 }
 
+/// Reader requesting the rest of the file from where it stopped when a read fails.
+///
+/// Bytes already read have been handed to the sink, so a resumed download appends to the partial
+/// file, and the checksum of the whole file is still verified once it completes.
+struct ResumingReader<'a, FTC> {
+    client: &'a FTC,
+    url: &'a str,
+    inner: Box<dyn Read + Send>,
+    offset: u64,
+    resumes_left: usize,
+}
+
+impl<FTC: FileTransferClient> Read for ResumingReader<'_, FTC> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.inner.read(buf) {
+                Ok(read) => {
+                    self.offset += read as u64;
+                    return Ok(read);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) if self.resumes_left > 0 => {
+                    self.resumes_left -= 1;
+                    tracing::debug!(
+                        offset = self.offset,
+                        "Download interrupted, resuming: {err}"
+                    );
+                    self.inner = self.client.get_reader_from(self.url, self.offset).map_err(
+                        |resume_err| {
+                            std::io::Error::other(format!("{err} (resuming failed: {resume_err})"))
+                        },
+                    )?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
 struct VerifyingReader<R: Read> {
     inner: R,
     hasher: sha2::Sha256,
@@ -188,10 +439,10 @@ fn validate_download(
 mod tests {
     use super::*;
     use crate::bundle::InMemoryBundleSources;
-    use crate::transfer::TransferError;
+    use crate::transfer::{PartialDownload, TransferError};
     use std::collections::HashMap;
     use std::io::{Cursor, Read};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
 
     #[derive(Clone)]
     struct MockClient {
@@ -325,4 +576,223 @@ mod tests {
             other => panic!("unexpected error: {other:?}"),
         }
     }
+
+    /// Reader failing with a connection reset after `fail_at` bytes.
+    struct BrokenReader {
+        data: Cursor<Vec<u8>>,
+        fail_at: u64,
+    }
+
+    impl Read for BrokenReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let left = self.fail_at.saturating_sub(self.data.position()) as usize;
+            if left == 0 {
+                return Err(std::io::ErrorKind::ConnectionReset.into());
+            }
+            let len = buf.len().min(left);
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    /// Client whose downloads break every `chunk` bytes but can be resumed.
+    #[derive(Clone)]
+    struct FlakyClient {
+        data: Vec<u8>,
+        chunk: u64,
+    }
+
+    impl FlakyClient {
+        fn reader_from(&self, offset: u64) -> Box<dyn Read + Send> {
+            let mut data = Cursor::new(self.data.clone());
+            data.set_position(offset);
+            let fail_at = (offset + self.chunk).min(self.data.len() as u64 + 1);
+            Box::new(BrokenReader { data, fail_at })
+        }
+    }
+
+    impl FileTransferClient for FlakyClient {
+        fn put_reader<R: Read + Send + 'static>(
+            &self,
+            _url: &str,
+            _reader: R,
+            _size_bytes: u64,
+        ) -> Result<(), TransferError> {
+            Ok(())
+        }
+
+        fn get_reader(&self, _url: &str) -> Result<Box<dyn Read + Send>, TransferError> {
+            Ok(self.reader_from(0))
+        }
+
+        fn get_reader_from(
+            &self,
+            _url: &str,
+            offset: u64,
+        ) -> Result<Box<dyn Read + Send>, TransferError> {
+            Ok(self.reader_from(offset))
+        }
+    }
+
+    fn flaky_download(chunk: u64) -> (Vec<u8>, Result<InMemoryBundleSources, DownloadError>) {
+        let data = b"0123456789abcdef".to_vec();
+        let client = FlakyClient {
+            data: data.clone(),
+            chunk,
+        };
+        let files = vec![ArtifactDownloadFile {
+            rel_path: "weights.bin".to_string(),
+            url: "mock://weights".to_string(),
+            size_bytes: Some(data.len() as u64),
+            checksum: Some(sha256_hex(&data)),
+        }];
+        let mut sink = InMemoryBundleSources::new();
+
+        let result = download_artifacts_to_sink_with_client(&client, &mut sink, &files);
+        (data, result.map(|()| sink))
+    }
+
+    #[test]
+    fn interrupted_download_is_resumed_from_the_last_byte() {
+        let (data, result) = flaky_download(5);
+
+        let sink = result.expect("download should resume");
+        assert_eq!(sink.files()[0].source(), data);
+    }
+
+    #[test]
+    fn download_fails_after_max_resumes() {
+        let (_, result) = flaky_download(2);
+
+        assert!(matches!(result, Err(DownloadError::TargetError(_))));
+    }
+
+    #[test]
+    fn partial_state_round_trips() {
+        let state = PartialState {
+            offset: 42,
+            etag: Some("\"v1\"".to_string()),
+        };
+
+        assert_eq!(PartialState::parse(&state.serialize()), Some(state));
+        assert_eq!(
+            PartialState::parse("offset=7\netag=\n"),
+            Some(PartialState {
+                offset: 7,
+                etag: None
+            })
+        );
+        assert_eq!(PartialState::parse("offset=seven\n"), None);
+    }
+
+    /// Offset and entity tag of every `get_partial` request.
+    type Requests = Arc<Mutex<Vec<(u64, Option<String>)>>>;
+
+    /// Client serving one version of a file, tagged `etag`, optionally breaking after `fail_at`
+    /// bytes without resuming.
+    #[derive(Clone)]
+    struct TaggedClient {
+        data: Vec<u8>,
+        etag: &'static str,
+        fail_at: Option<u64>,
+        requests: Requests,
+    }
+
+    impl TaggedClient {
+        fn new(data: &[u8], etag: &'static str, fail_at: Option<u64>) -> Self {
+            Self {
+                data: data.to_vec(),
+                etag,
+                fail_at,
+                requests: Arc::default(),
+            }
+        }
+    }
+
+    impl FileTransferClient for TaggedClient {
+        fn put_reader<R: Read + Send + 'static>(
+            &self,
+            _url: &str,
+            _reader: R,
+            _size_bytes: u64,
+        ) -> Result<(), TransferError> {
+            Ok(())
+        }
+
+        fn get_reader(&self, url: &str) -> Result<Box<dyn Read + Send>, TransferError> {
+            Ok(self.get_partial(url, 0, None)?.reader)
+        }
+
+        fn get_partial(
+            &self,
+            _url: &str,
+            offset: u64,
+            etag: Option<&str>,
+        ) -> Result<PartialDownload, TransferError> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((offset, etag.map(str::to_string)));
+            let offset = if etag == Some(self.etag) { offset } else { 0 };
+            let mut data = Cursor::new(self.data.clone());
+            data.set_position(offset);
+            let reader: Box<dyn Read + Send> = match self.fail_at {
+                Some(fail_at) => Box::new(BrokenReader { data, fail_at }),
+                None => Box::new(data),
+            };
+            Ok(PartialDownload {
+                offset,
+                etag: Some(self.etag.to_string()),
+                reader,
+            })
+        }
+    }
+
+    fn resumable_download(
+        client: &TaggedClient,
+        data: &[u8],
+        partial_dir: &Path,
+    ) -> Result<InMemoryBundleSources, DownloadError> {
+        let files = vec![ArtifactDownloadFile {
+            rel_path: "weights.bin".to_string(),
+            url: format!("mock://weights?signature={}", client.etag),
+            size_bytes: Some(data.len() as u64),
+            checksum: Some(sha256_hex(data)),
+        }];
+        let mut sink = InMemoryBundleSources::new();
+
+        download_artifacts_to_sink_resumable(client, &mut sink, &files, partial_dir).map(|()| sink)
+    }
+
+    #[test]
+    fn failed_download_is_resumed_by_a_later_call() {
+        let data = b"0123456789abcdef";
+        let partial_dir = tempfile::tempdir().unwrap();
+
+        let broken = TaggedClient::new(data, "\"v1\"", Some(6));
+        assert!(resumable_download(&broken, data, partial_dir.path()).is_err());
+
+        let client = TaggedClient::new(data, "\"v1\"", None);
+        let sink = resumable_download(&client, data, partial_dir.path()).expect("should resume");
+
+        assert_eq!(sink.files()[0].source(), data);
+        assert_eq!(
+            *client.requests.lock().unwrap(),
+            vec![(6, Some("\"v1\"".to_string()))]
+        );
+        assert_eq!(fs::read_dir(partial_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn partial_download_restarts_when_the_file_changed() {
+        let data = b"0123456789abcdef";
+        let partial_dir = tempfile::tempdir().unwrap();
+
+        let broken = TaggedClient::new(b"fedcba9876543210", "\"v1\"", Some(6));
+        assert!(resumable_download(&broken, data, partial_dir.path()).is_err());
+
+        let client = TaggedClient::new(data, "\"v2\"", None);
+        let sink = resumable_download(&client, data, partial_dir.path()).expect("should restart");
+
+        assert_eq!(sink.files()[0].source(), data);
+    }
 }
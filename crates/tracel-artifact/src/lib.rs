@@ -8,4 +8,7 @@ pub mod download;
 pub mod upload;
 
 pub use tools::validation::normalize_checksum;
-pub use transfer::{FileTransferClient, ReqwestTransferClient, TransferError};
+pub use transfer::{
+    FileTransferClient, PartialDownload, PoolSettings, ReqwestTransferClient,
+    ThrottledTransferClient, TransferError,
+};
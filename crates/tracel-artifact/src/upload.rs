@@ -1,11 +1,20 @@
 //! This module provides utilities for uploading artifact files from any source to any target bundle sink using multipart uploads with presigned URLs.
 //!
+//! Files are uploaded several at a time, see [`upload_bundle_multipart_concurrent`] for the concurrency and retry behavior.
+//!
 //! The upload process can be customized with any implementation of the FileTransferClient trait (e.g. for custom HTTP clients, authentication, retries, etc), and multipart file sources can be abstracted behind the MultipartUploadSource trait for maximum flexibility (e.g. to support streaming from large files without loading them fully into memory).
 
 use crate::transfer::TransferError;
 use crate::{FileTransferClient, ReqwestTransferClient};
 use std::collections::HashSet;
 use std::io::Read;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Upper bound of the delay between two attempts of a part upload.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
 
 /// Errors that can occur during artifact file uploads.
 #[derive(Debug, thiserror::Error)]
@@ -46,6 +55,79 @@ pub struct MultipartUploadFile {
     pub parts: Vec<MultipartUploadPart>,
 }
 
+/// Progress of a multipart upload, reported after each uploaded part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// Relative path of the file the last part belonged to.
+    pub rel_path: String,
+    /// Bytes uploaded so far, across all files.
+    pub uploaded_bytes: u64,
+    /// Total bytes to upload, across all files.
+    pub total_bytes: u64,
+}
+
+/// Phase of an artifact upload, reported to an [`UploadProgressSink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UploadPhase {
+    /// The artifact's files are being written into a bundle.
+    Packaging,
+    /// The upload was accepted by the server and its files are about to be sent.
+    Uploading {
+        /// Total bytes to upload, across all files.
+        total_bytes: u64,
+    },
+    /// Bytes uploaded so far, reported after each uploaded part.
+    Progress(UploadProgress),
+    /// Every file was uploaded and the artifact is being registered with the server.
+    Registering,
+    /// The artifact was uploaded and registered.
+    Finished,
+}
+
+/// Receives the phases of artifact uploads, e.g. to drive a progress bar.
+///
+/// Implemented for closures taking the artifact name and the phase. Uploads may run on background
+/// threads, several at a time, so the sink must tell them apart by name.
+pub trait UploadProgressSink: Send + Sync {
+    fn on_phase(&self, artifact: &str, phase: &UploadPhase);
+}
+
+impl<F> UploadProgressSink for F
+where
+    F: Fn(&str, &UploadPhase) + Send + Sync,
+{
+    fn on_phase(&self, artifact: &str, phase: &UploadPhase) {
+        self(artifact, phase)
+    }
+}
+
+/// [`UploadProgressSink`] logging upload phases with `tracing`.
+///
+/// The start and the end of every upload are logged at `info` level, the other phases at `debug`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingUploadProgress;
+
+impl UploadProgressSink for TracingUploadProgress {
+    fn on_phase(&self, artifact: &str, phase: &UploadPhase) {
+        match phase {
+            UploadPhase::Packaging => tracing::debug!(artifact, "Packaging artifact"),
+            UploadPhase::Uploading { total_bytes } => {
+                tracing::info!(artifact, total_bytes, "Uploading artifact")
+            }
+            UploadPhase::Progress(progress) => tracing::debug!(
+                artifact,
+                rel_path = progress.rel_path,
+                uploaded_bytes = progress.uploaded_bytes,
+                total_bytes = progress.total_bytes,
+                "Artifact upload progress"
+            ),
+            UploadPhase::Registering => tracing::debug!(artifact, "Registering artifact"),
+            UploadPhase::Finished => tracing::info!(artifact, "Artifact uploaded"),
+        }
+    }
+}
+
 /// Source abstraction for multipart uploads.
 pub trait MultipartUploadSource {
     /// Return the file length in bytes for a relative path.
@@ -60,8 +142,32 @@ pub trait MultipartUploadSource {
     ) -> Result<Box<dyn Read + Send>, UploadError>;
 }
 
+/// Concurrency and retry settings of [`upload_bundle_multipart_concurrent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadSettings {
+    /// Number of files uploaded at the same time.
+    pub concurrency: usize,
+    /// Extra attempts for a part whose transfer failed.
+    pub retries: usize,
+    /// Delay before the first retry of a part. It doubles with every further attempt, up to 30
+    /// seconds, and is randomized by up to half to keep concurrent uploads from retrying in step.
+    pub backoff: Duration,
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            retries: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
 /// Upload multiple files from a multipart source using presigned URLs.
-pub fn upload_bundle_multipart<S: MultipartUploadSource>(
+///
+/// Files are uploaded concurrently with the default [`UploadSettings`].
+pub fn upload_bundle_multipart<S: MultipartUploadSource + Sync>(
     source: &S,
     files: &[MultipartUploadFile],
 ) -> Result<(), UploadError> {
@@ -70,32 +176,140 @@ pub fn upload_bundle_multipart<S: MultipartUploadSource>(
 }
 
 /// Upload multiple files from a multipart source using presigned URLs and a custom client.
-pub fn upload_bundle_multipart_with_client<FTC: FileTransferClient, S: MultipartUploadSource>(
+///
+/// Files are uploaded concurrently with the default [`UploadSettings`].
+pub fn upload_bundle_multipart_with_client<
+    FTC: FileTransferClient,
+    S: MultipartUploadSource + Sync,
+>(
     client: &FTC,
     source: &S,
     files: &[MultipartUploadFile],
 ) -> Result<(), UploadError> {
-    let mut seen = HashSet::new();
+    upload_bundle_multipart_with_progress(client, source, files, |_| {})
+}
+
+/// Upload multiple files from a multipart source using a custom client, reporting progress.
+///
+/// Files are uploaded concurrently with the default [`UploadSettings`]. `on_progress` is called
+/// after every uploaded part, which lets callers drive a progress bar.
+pub fn upload_bundle_multipart_with_progress<FTC, S, P>(
+    client: &FTC,
+    source: &S,
+    files: &[MultipartUploadFile],
+    on_progress: P,
+) -> Result<(), UploadError>
+where
+    FTC: FileTransferClient,
+    S: MultipartUploadSource + Sync,
+    P: FnMut(UploadProgress) + Send,
+{
+    upload_bundle_multipart_concurrent(
+        client,
+        source,
+        files,
+        &UploadSettings::default(),
+        on_progress,
+    )
+}
+
+/// Upload multiple files from a multipart source, several files at a time.
+///
+/// Up to `settings.concurrency` files are uploaded in parallel, each by its own thread, while the
+/// parts of one file are uploaded in order. A part whose transfer fails is read again from the
+/// source and retried up to `settings.retries` times, as long as the error is retryable (see
+/// [`TransferError::is_retryable`]). Retries wait for an exponential backoff with jitter, or for
+/// the server's `Retry-After` delay when it is longer. After the first error no new file is
+/// started, and the error is returned once the uploads in flight are done.
+///
+/// `on_progress` is called after every uploaded part, from the uploading threads.
+pub fn upload_bundle_multipart_concurrent<FTC, S, P>(
+    client: &FTC,
+    source: &S,
+    files: &[MultipartUploadFile],
+    settings: &UploadSettings,
+    on_progress: P,
+) -> Result<(), UploadError>
+where
+    FTC: FileTransferClient,
+    S: MultipartUploadSource + Sync,
+    P: FnMut(UploadProgress) + Send,
+{
+    check_unique_paths(files)?;
+    let total_bytes = total_bytes(files);
+    let progress = Mutex::new((0u64, on_progress));
+    let next_file = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let upload_files = || -> Result<(), UploadError> {
+        while !failed.load(Ordering::Acquire) {
+            let Some(file) = files.get(next_file.fetch_add(1, Ordering::AcqRel)) else {
+                return Ok(());
+            };
+            let result = upload_source_file_multipart_streaming(
+                client,
+                source,
+                &file.rel_path,
+                &file.parts,
+                settings,
+                |size| {
+                    let mut progress = progress.lock().unwrap();
+                    let (uploaded_bytes, on_progress) = &mut *progress;
+                    *uploaded_bytes += size;
+                    on_progress(UploadProgress {
+                        rel_path: file.rel_path.clone(),
+                        uploaded_bytes: *uploaded_bytes,
+                        total_bytes,
+                    });
+                },
+            );
+            if result.is_err() {
+                failed.store(true, Ordering::Release);
+                return result;
+            }
+        }
+        Ok(())
+    };
+
+    let workers = settings.concurrency.clamp(1, files.len().max(1));
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(upload_files)).collect();
+        handles.into_iter().try_for_each(|handle| {
+            handle
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+        })
+    })
+}
 
+fn check_unique_paths(files: &[MultipartUploadFile]) -> Result<(), UploadError> {
+    let mut seen = HashSet::new();
     for file in files {
-        if !seen.insert(file.rel_path.clone()) {
+        if !seen.insert(file.rel_path.as_str()) {
             return Err(UploadError::InvalidMultipart(format!(
                 "Duplicate multipart upload descriptor for {}",
                 file.rel_path
             )));
         }
-
-        upload_source_file_multipart_streaming(client, source, &file.rel_path, &file.parts)?;
     }
-
     Ok(())
 }
 
+fn total_bytes(files: &[MultipartUploadFile]) -> u64 {
+    files
+        .iter()
+        .flat_map(|file| &file.parts)
+        .map(|part| part.size_bytes)
+        .sum()
+}
+
 fn upload_source_file_multipart_streaming<FTC: FileTransferClient, S: MultipartUploadSource>(
     client: &FTC,
     source: &S,
     rel_path: &str,
     parts: &[MultipartUploadPart],
+    settings: &UploadSettings,
+    mut on_part_uploaded: impl FnMut(u64),
 ) -> Result<(), UploadError> {
     let file_len = source.file_len(rel_path)?;
 
@@ -128,17 +342,42 @@ fn upload_source_file_multipart_streaming<FTC: FileTransferClient, S: MultipartU
             )));
         }
 
-        let reader = source.open_part(rel_path, offset, size)?;
-        client
-            .put_reader(&part.url, reader, size)
-            .map_err(|e| UploadError::Transfer {
-                part_index: part_index + 1,
-                total_parts: parts.len(),
-                rel_path: rel_path.to_string(),
-                source: e,
-            })?;
+        let mut attempt = 0;
+        loop {
+            let reader = source.open_part(rel_path, offset, size)?;
+            match client.put_reader(&part.url, reader, size) {
+                Ok(()) => break,
+                Err(err) if attempt < settings.retries && err.is_retryable() => {
+                    attempt += 1;
+                    let mut delay = retry_backoff(settings.backoff, attempt);
+                    if let TransferError::RateLimited {
+                        retry_after: Some(retry_after),
+                    } = err
+                    {
+                        delay = delay.max(retry_after);
+                    }
+                    tracing::debug!(
+                        rel_path,
+                        part = part_index + 1,
+                        attempt,
+                        delay_ms = delay.as_millis(),
+                        "Retrying part upload: {err}"
+                    );
+                    thread::sleep(delay);
+                }
+                Err(err) => {
+                    return Err(UploadError::Transfer {
+                        part_index: part_index + 1,
+                        total_parts: parts.len(),
+                        rel_path: rel_path.to_string(),
+                        source: err,
+                    });
+                }
+            }
+        }
 
         offset += size;
+        on_part_uploaded(size);
     }
 
     if offset != file_len {
@@ -151,6 +390,17 @@ fn upload_source_file_multipart_streaming<FTC: FileTransferClient, S: MultipartU
     Ok(())
 }
 
+/// Delay before retry number `attempt` (starting at 1): `base` doubled for every earlier
+/// attempt, capped at [`MAX_RETRY_BACKOFF`], then reduced by a random amount of up to half.
+fn retry_backoff(base: Duration, attempt: usize) -> Duration {
+    let exponent = u32::try_from(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+    let delay = base
+        .checked_mul(2u32.saturating_pow(exponent))
+        .unwrap_or(MAX_RETRY_BACKOFF)
+        .min(MAX_RETRY_BACKOFF);
+    delay.mul_f64(1.0 - fastrand::f64() / 2.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,9 +409,12 @@ mod tests {
     use std::io::{Cursor, Read};
     use std::sync::{Arc, Mutex};
 
+    /// Uploaded parts as (url, declared size, body).
+    type Puts = Arc<Mutex<Vec<(String, u64, Vec<u8>)>>>;
+
     #[derive(Clone, Default)]
     struct MockClient {
-        puts: Arc<Mutex<Vec<(String, u64, Vec<u8>)>>>,
+        puts: Puts,
     }
 
     impl FileTransferClient for MockClient {
@@ -333,4 +586,210 @@ mod tests {
         assert_eq!(puts[1], ("u2".to_string(), 2, b"cd".to_vec()));
         assert_eq!(puts[2], ("u3".to_string(), 2, b"ef".to_vec()));
     }
+
+    #[test]
+    fn reports_progress_after_each_part() {
+        let client = MockClient::default();
+        let source = MockSource::new(HashMap::from([
+            ("a.bin".to_string(), b"abcd".to_vec()),
+            ("b.bin".to_string(), b"ef".to_vec()),
+        ]));
+        let part = |part, url: &str, size_bytes| MultipartUploadPart {
+            part,
+            url: url.to_string(),
+            size_bytes,
+        };
+        let files = vec![
+            MultipartUploadFile {
+                rel_path: "a.bin".to_string(),
+                parts: vec![part(1, "a1", 2), part(2, "a2", 2)],
+            },
+            MultipartUploadFile {
+                rel_path: "b.bin".to_string(),
+                parts: vec![part(1, "b1", 2)],
+            },
+        ];
+
+        let mut progress = Vec::new();
+        upload_bundle_multipart_with_progress(&client, &source, &files, |p| progress.push(p))
+            .expect("valid multipart plan should upload");
+
+        let reported: Vec<(&str, u64, u64)> = progress
+            .iter()
+            .map(|p| (p.rel_path.as_str(), p.uploaded_bytes, p.total_bytes))
+            .collect();
+        assert_eq!(
+            reported,
+            vec![("a.bin", 2, 6), ("a.bin", 4, 6), ("b.bin", 6, 6)]
+        );
+    }
+
+    /// Client failing the first `failures` uploads to each URL, with `status` if set and with a
+    /// transport error otherwise.
+    #[derive(Clone, Default)]
+    struct FlakyClient {
+        inner: MockClient,
+        failures: usize,
+        status: Option<u16>,
+        attempts: Arc<Mutex<HashMap<String, usize>>>,
+    }
+
+    impl FileTransferClient for FlakyClient {
+        fn put_reader<R: Read + Send + 'static>(
+            &self,
+            url: &str,
+            reader: R,
+            size_bytes: u64,
+        ) -> Result<(), TransferError> {
+            let mut attempts = self.attempts.lock().expect("lock attempts");
+            let attempt = attempts.entry(url.to_string()).or_default();
+            *attempt += 1;
+            if *attempt <= self.failures {
+                return Err(match self.status {
+                    Some(status) => TransferError::Status {
+                        status,
+                        message: format!("status {status}"),
+                    },
+                    None => TransferError::Transport("connection reset".to_string()),
+                });
+            }
+            drop(attempts);
+            self.inner.put_reader(url, reader, size_bytes)
+        }
+
+        fn get_reader(&self, url: &str) -> Result<Box<dyn Read + Send>, TransferError> {
+            self.inner.get_reader(url)
+        }
+    }
+
+    fn single_part_files(count: usize) -> (MockSource, Vec<MultipartUploadFile>) {
+        let names: Vec<String> = (0..count).map(|i| format!("file-{i}.bin")).collect();
+        let source = MockSource::new(
+            names
+                .iter()
+                .map(|name| (name.clone(), name.as_bytes().to_vec()))
+                .collect(),
+        );
+        let files = names
+            .iter()
+            .map(|name| MultipartUploadFile {
+                rel_path: name.clone(),
+                parts: vec![MultipartUploadPart {
+                    part: 1,
+                    url: name.clone(),
+                    size_bytes: name.len() as u64,
+                }],
+            })
+            .collect();
+        (source, files)
+    }
+
+    #[test]
+    fn concurrent_upload_sends_every_file_once() {
+        let client = MockClient::default();
+        let (source, files) = single_part_files(10);
+        let settings = UploadSettings {
+            concurrency: 3,
+            retries: 0,
+            backoff: Duration::ZERO,
+        };
+
+        let mut last = None;
+        upload_bundle_multipart_concurrent(&client, &source, &files, &settings, |p| last = Some(p))
+            .expect("valid multipart plan should upload");
+
+        let mut urls: Vec<String> = client
+            .puts
+            .lock()
+            .expect("lock puts")
+            .iter()
+            .map(|(url, _, bytes)| {
+                assert_eq!(url.as_bytes(), bytes);
+                url.clone()
+            })
+            .collect();
+        urls.sort();
+        let mut expected: Vec<String> = files.iter().map(|f| f.rel_path.clone()).collect();
+        expected.sort();
+        assert_eq!(urls, expected);
+
+        let last = last.expect("progress reported");
+        assert_eq!(last.uploaded_bytes, last.total_bytes);
+    }
+
+    /// Default settings without the delay between attempts.
+    fn without_backoff() -> UploadSettings {
+        UploadSettings {
+            backoff: Duration::ZERO,
+            ..UploadSettings::default()
+        }
+    }
+
+    #[test]
+    fn concurrent_upload_retries_failed_parts() {
+        let client = FlakyClient {
+            failures: 2,
+            ..Default::default()
+        };
+        let (source, files) = single_part_files(2);
+
+        upload_bundle_multipart_concurrent(&client, &source, &files, &without_backoff(), |_| {})
+            .expect("parts should succeed within the retry budget");
+        assert_eq!(client.inner.puts.lock().expect("lock puts").len(), 2);
+
+        let client = FlakyClient {
+            failures: 1,
+            ..Default::default()
+        };
+        let settings = UploadSettings {
+            concurrency: 1,
+            retries: 0,
+            backoff: Duration::ZERO,
+        };
+        let err = upload_bundle_multipart_concurrent(&client, &source, &files, &settings, |_| {})
+            .expect_err("no retries left");
+
+        assert!(matches!(err, UploadError::Transfer { .. }));
+        assert_eq!(client.attempts.lock().expect("lock attempts").len(), 1);
+    }
+
+    #[test]
+    fn concurrent_upload_retries_server_errors_only() {
+        let (source, files) = single_part_files(1);
+        let upload = |status| {
+            let client = FlakyClient {
+                failures: 1,
+                status: Some(status),
+                ..Default::default()
+            };
+            let result = upload_bundle_multipart_concurrent(
+                &client,
+                &source,
+                &files,
+                &without_backoff(),
+                |_| {},
+            );
+            let attempts = client.attempts.lock().expect("lock attempts")["file-0.bin"];
+            (result.is_ok(), attempts)
+        };
+
+        assert_eq!(upload(503), (true, 2));
+        assert_eq!(upload(403), (false, 1));
+    }
+
+    #[test]
+    fn retry_backoff_doubles_with_jitter_up_to_the_cap() {
+        let base = Duration::from_millis(100);
+        for attempt in 1..=4 {
+            let full = base * 2u32.pow(attempt as u32 - 1);
+            let delay = retry_backoff(base, attempt);
+            assert!(
+                delay <= full && delay >= full / 2,
+                "attempt {attempt}: {delay:?}"
+            );
+        }
+
+        assert!(retry_backoff(base, 64) <= MAX_RETRY_BACKOFF);
+        assert!(retry_backoff(base, 64) >= MAX_RETRY_BACKOFF / 2);
+    }
 }
@@ -1,13 +1,23 @@
+use std::num::NonZeroU32;
 use std::path::Path;
 
 use serde::Deserialize;
-use tracel_artifact::ReqwestTransferClient;
+use serde::de::DeserializeOwned;
+use tracel_artifact::{ReqwestTransferClient, ThrottledTransferClient};
 use tracel_client::{Client, ClientError, Env, TracelCredentials};
 
+use crate::environment::EnvironmentConfig;
+use crate::notification::NotificationConfig;
+
 const TRACEL_ENV: &str = "TRACEL_ENV";
 const TRACEL_PROJECT: &str = "TRACEL_PROJECT";
 const TRACEL_NAMESPACE: &str = "TRACEL_NAMESPACE";
 const TRACEL_API_KEY: &str = "TRACEL_API_KEY";
+const TRACEL_ACCOUNT: &str = "TRACEL_ACCOUNT";
+const TRACEL_CONSOLE_URL: &str = "TRACEL_CONSOLE_URL";
+const TRACEL_TRANSFER_RATE_LIMIT: &str = "TRACEL_TRANSFER_RATE_LIMIT";
+
+const PRODUCTION_CONSOLE_URL: &str = "https://console.tracel.ai";
 
 #[derive(Debug, thiserror::Error)]
 pub enum CloudError {
@@ -19,6 +29,8 @@ pub enum CloudError {
     NoNamespace,
     #[error("No project found: set {TRACEL_PROJECT} or add project to tracel.toml")]
     NoProject,
+    #[error("Invalid account name '{0}': use only letters, digits, '-' and '_'")]
+    InvalidAccount(String),
     #[error("Invalid environment variable {env_var}: {message}")]
     InvalidEnv { env_var: String, message: String },
     #[error("could not determine a cache directory for downloaded models")]
@@ -27,13 +39,20 @@ pub enum CloudError {
     Client(#[from] ClientError),
 }
 
+/// Transfer client used for artifact and model files, throttled to the configured rate limit.
+///
+/// Without a configured limit, requests are only held back while the server asks to wait.
+pub(crate) type CloudTransferClient = ThrottledTransferClient<ReqwestTransferClient>;
+
 #[derive(Clone)]
 pub struct CloudBackend {
     pub(crate) client: Client,
     pub(crate) namespace: String,
     pub(crate) project: String,
-    pub(crate) file_transfer_client: ReqwestTransferClient,
+    pub(crate) file_transfer_client: CloudTransferClient,
     pub(crate) model_cache: crate::model_registry::ModelCache,
+    /// Base URL of the web console, used to link experiments when they start.
+    pub(crate) console_url: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -42,15 +61,51 @@ struct CliCredentials {
 }
 
 #[derive(Deserialize, Default)]
-struct TracelTomlConfig {
+pub(crate) struct TracelTomlConfig {
     #[serde(alias = "owner")]
     namespace: Option<String>,
     #[serde(alias = "name")]
     project: Option<String>,
+    /// Named account whose stored credentials this project uses.
+    ///
+    /// Parsed on its own by [`read_tracel_toml`], so a bad value does not hide the project.
+    #[serde(skip)]
+    account: Option<String>,
+    /// Parsed on its own by [`read_tracel_toml`], so a bad section does not hide the others.
+    #[serde(skip)]
+    pub(crate) notifications: NotificationConfig,
+    /// Parsed on its own by [`read_tracel_toml`], so a bad section does not hide the others.
+    #[serde(skip)]
+    pub(crate) environment: EnvironmentConfig,
+    /// Parsed on its own by [`read_tracel_toml`], so a bad section does not hide the others.
+    #[serde(skip)]
+    transfer: TransferConfig,
+}
+
+impl TracelTomlConfig {
+    /// Project name, from the environment or else `tracel.toml`.
+    pub(crate) fn project_name(&self) -> Option<String> {
+        std::env::var(TRACEL_PROJECT)
+            .ok()
+            .or_else(|| self.project.clone())
+    }
+}
+
+/// Artifact transfer settings, read from the `[transfer]` table of `tracel.toml`.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct TransferConfig {
+    /// Maximum artifact transfer requests per second. Unlimited when unset.
+    rate_limit: Option<NonZeroU32>,
 }
 
 impl CloudBackend {
-    fn new(client: Client, namespace: String, project: String) -> Result<Self, CloudError> {
+    fn new(
+        client: Client,
+        namespace: String,
+        project: String,
+        console_url: Option<String>,
+        transfer_rate_limit: Option<u32>,
+    ) -> Result<Self, CloudError> {
         let cache_root = crate::model_registry::resolve_cache_dir()
             .ok_or(CloudError::NoCacheDir)?
             .join("cloud")
@@ -62,8 +117,12 @@ impl CloudBackend {
             client,
             namespace,
             project,
-            file_transfer_client: ReqwestTransferClient::new(),
+            file_transfer_client: match transfer_rate_limit {
+                Some(limit) => ThrottledTransferClient::new(ReqwestTransferClient::new(), limit),
+                None => ThrottledTransferClient::unthrottled(ReqwestTransferClient::new()),
+            },
             model_cache: crate::model_registry::ModelCache::new(cache_root),
+            console_url,
         })
     }
 
@@ -71,6 +130,12 @@ impl CloudBackend {
         let env = discover_env()?;
         let credentials = discover_credentials(&env)?;
         let (namespace, project) = discover_namespace_project()?;
+        let console_url = console_url(&env, std::env::var(TRACEL_CONSOLE_URL).ok());
+        let transfer_rate_limit =
+            match transfer_rate_limit(std::env::var(TRACEL_TRANSFER_RATE_LIMIT).ok())? {
+                Some(limit) => Some(limit),
+                None => read_tracel_toml().transfer.rate_limit.map(NonZeroU32::get),
+            };
 
         let client = Client::new(env, &credentials).map_err(|err| {
             if err.is_login_error() {
@@ -79,10 +144,35 @@ impl CloudBackend {
                 CloudError::Client(err)
             }
         })?;
-        CloudBackend::new(client, namespace, project)
+        CloudBackend::new(client, namespace, project, console_url, transfer_rate_limit)
     }
 }
 
+/// Maximum artifact transfer requests per second set in the environment, if any.
+///
+/// Transfers are only throttled client-side when a limit is set here or in the `[transfer]`
+/// table of `tracel.toml`.
+fn transfer_rate_limit(from_env: Option<String>) -> Result<Option<u32>, CloudError> {
+    let Some(value) = from_env else {
+        return Ok(None);
+    };
+    value
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|limit| *limit > 0)
+        .map(Some)
+        .ok_or_else(|| CloudError::InvalidEnv {
+            env_var: TRACEL_TRANSFER_RATE_LIMIT.to_string(),
+            message: "expected a positive number of requests per second".to_string(),
+        })
+}
+
+/// The console URL set in the environment, or the production console when targeting production.
+fn console_url(env: &Env, from_env: Option<String>) -> Option<String> {
+    from_env.or_else(|| matches!(env, Env::Production).then(|| PRODUCTION_CONSOLE_URL.to_string()))
+}
+
 fn discover_credentials(env: &Env) -> Result<TracelCredentials, CloudError> {
     if let Ok(creds) = TracelCredentials::from_env() {
         return Ok(creds);
@@ -91,11 +181,10 @@ fn discover_credentials(env: &Env) -> Result<TracelCredentials, CloudError> {
     let proj_dirs = directories::ProjectDirs::from("ai", "tracel", "console")
         .ok_or(CloudError::NoCredentials)?;
 
-    let filename = match env {
-        Env::Production => "credentials.json".to_string(),
-        Env::Staging(v) => format!("credentials-staging{v}.json"),
-        Env::Development => "credentials-dev.json".to_string(),
-    };
+    let account = std::env::var(TRACEL_ACCOUNT)
+        .ok()
+        .or_else(|| read_tracel_toml().account);
+    let filename = credentials_filename(env, account.as_deref())?;
 
     let path = proj_dirs.config_dir().join(&filename);
     if path.exists() {
@@ -108,6 +197,32 @@ fn discover_credentials(env: &Env) -> Result<TracelCredentials, CloudError> {
     Err(CloudError::NoCredentials)
 }
 
+/// Name of the stored credentials file for an environment and optional named account.
+///
+/// The default account keeps the historical file names, so existing logins keep working. Account
+/// names are limited to `[A-Za-z0-9_-]`, so they cannot point outside the config directory.
+fn credentials_filename(env: &Env, account: Option<&str>) -> Result<String, CloudError> {
+    let env_suffix = match env {
+        Env::Production => String::new(),
+        Env::Staging(v) => format!("-staging{v}"),
+        Env::Development => "-dev".to_string(),
+    };
+    match account {
+        Some(account) if is_valid_account(account) => {
+            Ok(format!("credentials{env_suffix}-{account}.json"))
+        }
+        Some(account) => Err(CloudError::InvalidAccount(account.to_string())),
+        None => Ok(format!("credentials{env_suffix}.json")),
+    }
+}
+
+fn is_valid_account(account: &str) -> bool {
+    !account.is_empty()
+        && account
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 fn discover_namespace_project() -> Result<(String, String), CloudError> {
     let namespace_env = std::env::var(TRACEL_NAMESPACE).ok();
     let project_env = std::env::var(TRACEL_PROJECT).ok();
@@ -151,7 +266,7 @@ fn discover_env() -> Result<Env, CloudError> {
     }
 }
 
-fn read_tracel_toml() -> TracelTomlConfig {
+pub(crate) fn read_tracel_toml() -> TracelTomlConfig {
     let path = Path::new("tracel.toml");
     if !path.exists() {
         return TracelTomlConfig::default();
@@ -159,5 +274,147 @@ fn read_tracel_toml() -> TracelTomlConfig {
     let Ok(contents) = std::fs::read_to_string(path) else {
         return TracelTomlConfig::default();
     };
-    toml::from_str(&contents).unwrap_or_default()
+    parse_tracel_toml(&contents)
+}
+
+/// Parse `tracel.toml`, keeping every part that is valid.
+///
+/// The account and the optional sections are parsed separately from the project fields: an
+/// invalid `[notifications]` table is logged and ignored instead of losing the namespace and
+/// project.
+fn parse_tracel_toml(contents: &str) -> TracelTomlConfig {
+    let mut table: toml::Table = match toml::from_str(contents) {
+        Ok(table) => table,
+        Err(err) => {
+            tracing::warn!("Ignoring tracel.toml, it is not valid TOML: {err}");
+            return TracelTomlConfig::default();
+        }
+    };
+    let account = toml_section(&mut table, "account");
+    let notifications = toml_section(&mut table, "notifications");
+    let environment = toml_section(&mut table, "environment");
+    let transfer = toml_section(&mut table, "transfer");
+
+    let config = toml::Value::Table(table).try_into().unwrap_or_else(|err| {
+        tracing::warn!("Ignoring the project settings of tracel.toml: {err}");
+        TracelTomlConfig::default()
+    });
+    TracelTomlConfig {
+        account,
+        notifications,
+        environment,
+        transfer,
+        ..config
+    }
+}
+
+fn toml_section<T: DeserializeOwned + Default>(table: &mut toml::Table, name: &str) -> T {
+    let Some(value) = table.remove(name) else {
+        return T::default();
+    };
+    value.try_into().unwrap_or_else(|err| {
+        tracing::warn!("Ignoring invalid `{name}` in tracel.toml: {err}");
+        T::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn console_url_defaults_to_production_only() {
+        assert_eq!(
+            console_url(&Env::Production, None).as_deref(),
+            Some(PRODUCTION_CONSOLE_URL)
+        );
+        assert_eq!(console_url(&Env::Development, None), None);
+        assert_eq!(
+            console_url(
+                &Env::Staging(1),
+                Some("https://staging.example".to_string())
+            )
+            .as_deref(),
+            Some("https://staging.example")
+        );
+    }
+
+    #[test]
+    fn transfer_rate_limit_is_read_from_the_environment() {
+        assert_eq!(transfer_rate_limit(None).unwrap(), None);
+        assert_eq!(
+            transfer_rate_limit(Some("10".to_string())).unwrap(),
+            Some(10)
+        );
+        assert!(transfer_rate_limit(Some("0".to_string())).is_err());
+        assert!(transfer_rate_limit(Some("fast".to_string())).is_err());
+    }
+
+    #[test]
+    fn transfer_rate_limit_is_opt_in() {
+        assert_eq!(parse_tracel_toml("").transfer.rate_limit, None);
+
+        let config = parse_tracel_toml("[transfer]\nrate_limit = 20\n");
+        assert_eq!(config.transfer.rate_limit.map(NonZeroU32::get), Some(20));
+
+        let config = parse_tracel_toml("project = \"vision\"\n\n[transfer]\nrate_limit = 0\n");
+        assert_eq!(config.transfer.rate_limit, None);
+        assert_eq!(config.project.as_deref(), Some("vision"));
+    }
+
+    #[test]
+    fn invalid_sections_do_not_hide_the_project() {
+        let config = parse_tracel_toml(
+            "namespace = \"acme\"\nproject = \"vision\"\naccount = 7\n\n[notifications]\n\
+             webhook = 3\n\n[environment]\nreport = true\n",
+        );
+
+        assert_eq!(config.namespace.as_deref(), Some("acme"));
+        assert_eq!(config.project.as_deref(), Some("vision"));
+        assert_eq!(config.account, None);
+        assert!(config.notifications.webhook.is_none());
+        assert!(config.environment.reporter().is_some());
+    }
+
+    #[test]
+    fn default_account_keeps_historical_credentials_filenames() {
+        assert_eq!(
+            credentials_filename(&Env::Production, None).unwrap(),
+            "credentials.json"
+        );
+        assert_eq!(
+            credentials_filename(&Env::Staging(2), None).unwrap(),
+            "credentials-staging2.json"
+        );
+        assert_eq!(
+            credentials_filename(&Env::Development, None).unwrap(),
+            "credentials-dev.json"
+        );
+    }
+
+    #[test]
+    fn named_accounts_get_their_own_credentials_file() {
+        assert_eq!(
+            credentials_filename(&Env::Production, Some("work")).unwrap(),
+            "credentials-work.json"
+        );
+        assert_eq!(
+            credentials_filename(&Env::Development, Some("work")).unwrap(),
+            "credentials-dev-work.json"
+        );
+    }
+
+    #[test]
+    fn account_names_cannot_leave_the_config_directory() {
+        for account in ["", "../work", "work/other", "work.json", "wörk"] {
+            assert!(
+                matches!(
+                    credentials_filename(&Env::Production, Some(account)),
+                    Err(CloudError::InvalidAccount(_))
+                ),
+                "{account:?}"
+            );
+        }
+        assert!(credentials_filename(&Env::Production, Some("Team_2-ci")).is_ok());
+    }
 }
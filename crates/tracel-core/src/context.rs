@@ -1,9 +1,15 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
+use crate::backend::cloud::read_tracel_toml;
 use crate::connection::{Connection, ContextError};
+use crate::environment::EnvironmentReporter;
 use crate::model_registry::{ModelRegistryModule, ModelRegistryProvider};
+use crate::notification::WebhookNotifier;
+use crate::pretrained::{PretrainedError, PretrainedSource};
+use tracel_artifact::bundle::BundleDecode;
 use tracel_experiment::ExperimentModule;
 use tracel_experiment::ExperimentProvider;
+use tracel_experiment::ExperimentRun;
 use tracel_inference::{InferenceModule, InferenceProvider};
 
 #[derive(Clone)]
@@ -11,6 +17,31 @@ pub struct Context {
     experiment_provider: Arc<dyn ExperimentProvider>,
     inference_provider: Arc<dyn InferenceProvider>,
     model_registry_provider: Option<Arc<dyn ModelRegistryProvider>>,
+    /// Read from `tracel.toml` the first time an experiment module is created.
+    experiment_hooks: Arc<OnceLock<ExperimentHooks>>,
+}
+
+/// Experiment hooks configured in `tracel.toml`.
+struct ExperimentHooks {
+    notifier: Option<WebhookNotifier>,
+    environment_reporter: Option<EnvironmentReporter>,
+}
+
+impl ExperimentHooks {
+    fn from_tracel_toml() -> Self {
+        let config = read_tracel_toml();
+        let project = config.project_name();
+        Self {
+            notifier: config
+                .notifications
+                .notifier()
+                .map(|notifier| match project {
+                    Some(project) => notifier.project(project),
+                    None => notifier,
+                }),
+            environment_reporter: config.environment.reporter(),
+        }
+    }
 }
 
 impl Context {
@@ -20,11 +51,27 @@ impl Context {
             experiment_provider: providers.experiment,
             inference_provider: providers.inference,
             model_registry_provider: providers.model_registry,
+            experiment_hooks: Arc::new(OnceLock::new()),
         })
     }
 
+    /// Create an experiment module for this context.
+    ///
+    /// If `tracel.toml` configures a `[notifications]` webhook, the module notifies it of job
+    /// lifecycle events. If it enables `[environment]` reports, every run saves an
+    /// `environment` artifact describing where it ran.
     pub fn experiment(&self) -> ExperimentModule {
-        ExperimentModule::new(self.experiment_provider.clone())
+        let hooks = self
+            .experiment_hooks
+            .get_or_init(ExperimentHooks::from_tracel_toml);
+        let mut module = ExperimentModule::new(self.experiment_provider.clone());
+        if let Some(notifier) = &hooks.notifier {
+            module = module.with_hook(notifier.clone());
+        }
+        if let Some(reporter) = &hooks.environment_reporter {
+            module = module.with_hook(reporter.clone());
+        }
+        module
     }
 
     pub fn inference(&self) -> InferenceModule {
@@ -36,4 +83,16 @@ impl Context {
             .clone()
             .map(ModelRegistryModule::new)
     }
+
+    /// Load pretrained weights for `run` from a previous experiment or the model registry.
+    ///
+    /// See [`PretrainedSource`] for the accepted sources and how lineage is recorded.
+    pub fn load_pretrained<D: BundleDecode>(
+        &self,
+        run: &ExperimentRun,
+        source: &PretrainedSource,
+        settings: &D::Settings,
+    ) -> Result<D, PretrainedError> {
+        source.load(run, self.models(), settings)
+    }
 }
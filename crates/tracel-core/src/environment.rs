@@ -0,0 +1,290 @@
+//! Reproducibility reports describing the environment a job ran in.
+//!
+//! [`EnvironmentReporter`] is an [`ExperimentHook`] that saves an `environment` artifact before the
+//! job function runs, so the report exists however the run ends. The artifact holds an
+//! [`EnvironmentReport`] with the SDK version, platform, Rust toolchain, a hash of the project's
+//! `Cargo.lock`, the backend and the build-relevant environment variables. It can be registered
+//! explicitly with [`tracel_experiment::ExperimentModule::with_hook`], or enabled per project in
+//! `tracel.toml`:
+//!
+//! ```toml
+//! [environment]
+//! report = true
+//! backend = "cuda"
+//! env = ["OMP_NUM_THREADS"]
+//! ```
+//!
+//! Only `RUST*`, `CARGO_*`, `CUDA_*` and `TRACEL_*` variables are reported, plus the ones listed
+//! in `env`. Credentials and URLs, such as `TRACEL_WEBHOOK_URL`, are left out even when they
+//! match.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracel_artifact::bundle::{BundleDecode, BundleEncode, BundleSink, BundleSource};
+use tracel_experiment::{ArtifactKind, ExperimentHook, ExperimentRunHandle};
+
+const ARTIFACT_NAME: &str = "environment";
+const REPORT_FILE: &str = "environment.json";
+/// Environment variables starting with one of these are reported.
+const REPORTED_PREFIXES: &[&str] = &["RUST", "CARGO_", "CUDA_", "TRACEL_"];
+/// Environment variables whose name contains one of these are left out of reports.
+const SECRET_MARKERS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "URL",
+    "WEBHOOK",
+];
+
+/// Per-project report settings, read from the `[environment]` table of `tracel.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct EnvironmentConfig {
+    #[serde(default)]
+    pub(crate) report: bool,
+    pub(crate) backend: Option<String>,
+    #[serde(default)]
+    pub(crate) env: Vec<String>,
+}
+
+impl EnvironmentConfig {
+    pub(crate) fn reporter(&self) -> Option<EnvironmentReporter> {
+        self.report.then(|| EnvironmentReporter {
+            backend: self.backend.clone(),
+            env: self.env.clone(),
+        })
+    }
+}
+
+/// Machine-readable description of the environment a job ran in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    /// Version of the Tracel SDK the job was built with.
+    pub sdk_version: String,
+    /// Operating system, as in [`std::env::consts::OS`].
+    pub os: String,
+    /// CPU architecture, as in [`std::env::consts::ARCH`].
+    pub arch: String,
+    /// Rust toolchain selected by rustup, when the job was started through it.
+    pub toolchain: Option<String>,
+    /// SHA-256 of the closest `Cargo.lock` above the working directory, if any.
+    pub lockfile_sha256: Option<String>,
+    /// Backend the job was configured to run on, if known.
+    pub backend: Option<String>,
+    /// Build-relevant environment variables of the job process, without credentials.
+    pub env: BTreeMap<String, String>,
+}
+
+impl EnvironmentReport {
+    /// Capture the environment of the current process.
+    ///
+    /// Besides the `RUST*`, `CARGO_*`, `CUDA_*` and `TRACEL_*` variables, the variables named in
+    /// `extra_env` are reported.
+    pub fn capture(backend: Option<String>, extra_env: &[String]) -> Self {
+        let env = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+
+        Self {
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            toolchain: std::env::var("RUSTUP_TOOLCHAIN").ok(),
+            lockfile_sha256: std::env::current_dir()
+                .ok()
+                .and_then(|dir| lockfile_sha256(&dir)),
+            backend,
+            env: reported_env(env, extra_env),
+        }
+    }
+}
+
+impl BundleEncode for EnvironmentReport {
+    type Settings = ();
+    type Error = String;
+
+    fn encode<O: BundleSink>(self, sink: &mut O, _settings: &()) -> Result<(), Self::Error> {
+        let json = serde_json::to_vec_pretty(&self)
+            .map_err(|e| format!("Failed to serialize environment report: {e}"))?;
+        sink.put_bytes(REPORT_FILE, &json)
+    }
+}
+
+impl BundleDecode for EnvironmentReport {
+    type Settings = ();
+    type Error = String;
+
+    fn decode<I: BundleSource>(source: &I, _settings: &()) -> Result<Self, Self::Error> {
+        let reader = source.open(REPORT_FILE)?;
+        serde_json::from_reader(reader)
+            .map_err(|e| format!("Failed to parse environment report: {e}"))
+    }
+}
+
+/// Experiment hook that saves an [`EnvironmentReport`] artifact before the job function runs.
+///
+/// Saving is best effort: failures are logged and never affect the run.
+#[derive(Debug, Clone, Default)]
+pub struct EnvironmentReporter {
+    backend: Option<String>,
+    env: Vec<String>,
+}
+
+impl EnvironmentReporter {
+    /// Create a reporter that does not record a backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `backend` as the backend the job runs on.
+    #[must_use]
+    pub fn backend(mut self, backend: impl Into<String>) -> Self {
+        self.backend = Some(backend.into());
+        self
+    }
+
+    /// Also report the environment variable `name`.
+    #[must_use]
+    pub fn env_var(mut self, name: impl Into<String>) -> Self {
+        self.env.push(name.into());
+        self
+    }
+
+    fn save(&self, run: &ExperimentRunHandle) {
+        let report = EnvironmentReport::capture(self.backend.clone(), &self.env);
+        if let Err(err) = run.save_artifact(ARTIFACT_NAME, ArtifactKind::Other, report, &()) {
+            tracing::warn!(
+                experiment_id = %run.id(),
+                "Failed to save environment report: {err}"
+            );
+        }
+    }
+}
+
+impl ExperimentHook for EnvironmentReporter {
+    fn before_run(&self, run: &ExperimentRunHandle) {
+        self.save(run);
+    }
+}
+
+fn lockfile_sha256(dir: &Path) -> Option<String> {
+    let lockfile = dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())?;
+    let bytes = std::fs::read(lockfile).ok()?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn reported_env(
+    vars: impl IntoIterator<Item = (String, String)>,
+    extra: &[String],
+) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .filter(|(name, _)| {
+            REPORTED_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+                || extra.contains(name)
+        })
+        .filter(|(name, _)| {
+            let name = name.to_ascii_uppercase();
+            !SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tracel_artifact::bundle::FsBundle;
+
+    use super::*;
+
+    #[test]
+    fn only_allowed_variables_are_reported() {
+        let vars = [
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/me"),
+            ("TRACEL_API_KEY", "k"),
+            ("TRACEL_PROJECT", "p"),
+            ("TRACEL_WEBHOOK_URL", "https://hooks.example.com/t0k3n"),
+            ("TRACEL_TRANSFER_URL", "https://u:p@example.com"),
+            ("CARGO_REGISTRY_TOKEN", "t"),
+            ("CUDA_VISIBLE_DEVICES", "0"),
+            ("OMP_NUM_THREADS", "8"),
+            ("DB_PASSWORD", "p"),
+            ("RUST_LOG", "info"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        let extra = ["OMP_NUM_THREADS", "DB_PASSWORD", "TRACEL_WEBHOOK_URL"].map(String::from);
+
+        let env = reported_env(vars, &extra);
+
+        assert_eq!(
+            env.keys().collect::<Vec<_>>(),
+            [
+                "CUDA_VISIBLE_DEVICES",
+                "OMP_NUM_THREADS",
+                "RUST_LOG",
+                "TRACEL_PROJECT"
+            ]
+        );
+    }
+
+    #[test]
+    fn webhook_url_is_never_reported() {
+        let vars = [(
+            "TRACEL_WEBHOOK_URL".to_string(),
+            "https://hooks.example.com/t0k3n".to_string(),
+        )];
+        let extra = ["TRACEL_WEBHOOK_URL".to_string()];
+
+        assert!(reported_env(vars, &extra).is_empty());
+    }
+
+    #[test]
+    fn lockfile_is_found_in_a_parent_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("crates/job");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(lockfile_sha256(&nested), None);
+
+        std::fs::write(root.path().join("Cargo.lock"), "version = 4\n").unwrap();
+
+        let hash = lockfile_sha256(&nested).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(lockfile_sha256(root.path()), Some(hash));
+    }
+
+    #[test]
+    fn report_round_trips_through_a_bundle() {
+        let report = EnvironmentReport::capture(Some("wgpu".to_string()), &[]);
+        let mut bundle = FsBundle::temp().unwrap();
+
+        report.clone().encode(&mut bundle, &()).unwrap();
+
+        assert_eq!(EnvironmentReport::decode(&bundle, &()).unwrap(), report);
+    }
+
+    #[test]
+    fn config_enables_the_reporter() {
+        let config: EnvironmentConfig = toml::from_str("backend = \"cuda\"").unwrap();
+        assert!(config.reporter().is_none());
+
+        let config: EnvironmentConfig =
+            toml::from_str("report = true\nbackend = \"cuda\"\nenv = [\"OMP_NUM_THREADS\"]")
+                .unwrap();
+        let reporter = config.reporter().unwrap();
+        assert_eq!(reporter.backend.as_deref(), Some("cuda"));
+        assert_eq!(reporter.env, ["OMP_NUM_THREADS"]);
+    }
+}
@@ -3,8 +3,9 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
 
-use crossbeam::channel::{Sender, unbounded};
+use crossbeam::channel::{RecvTimeoutError, Sender, unbounded};
 use tracel_artifact::bundle::FsBundle;
 
 use std::collections::HashMap;
@@ -17,11 +18,18 @@ use tracel_experiment::error::{ExperimentError, ExperimentErrorKind};
 use tracel_experiment::reader::{
     ArtifactRef, ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact,
 };
-use tracel_experiment::session::{BundleFn, Event, ExperimentCompletion, ExperimentSession};
-use tracel_experiment::{ArtifactKind, ExperimentId};
+use tracel_experiment::session::{
+    BundleFn, Event, ExperimentCompletion, ExperimentFailure, ExperimentSession, FailureClass,
+};
+use tracel_experiment::{ArtifactKind, ExperimentId, LogRecord};
 
 use crate::backend::local::LocalBackend;
 
+/// How often an active local run refreshes its `heartbeat` file.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Age of the last heartbeat after which an unfinished run is considered dead.
+const STALE_AFTER: Duration = Duration::from_secs(3 * 30);
+
 impl ExperimentProvider for LocalBackend {
     fn create_experiment(
         &self,
@@ -46,6 +54,7 @@ fn create_experiment_run(root: PathBuf) -> Result<ExperimentRun, ExperimentError
                 err,
             )
         })?;
+    finalize_stale_runs(&root, STALE_AFTER);
     let (id, run_root) = create_local_run_dir(&root).map_err(|err| {
         ExperimentError::with_source(
             ExperimentErrorKind::Internal,
@@ -74,10 +83,15 @@ struct LocalExperimentSession {
 impl LocalExperimentSession {
     fn new(root: PathBuf) -> Result<Self, std::io::Error> {
         fs::create_dir_all(root.join("artifacts"))?;
+        fs::create_dir_all(root.join("logs"))?;
         let (sender, receiver) = unbounded();
-        let events_path = root.join("events.log");
-        let status_path = root.join("status.txt");
-        let join = thread::spawn(move || local_worker(receiver, events_path, status_path));
+        let paths = LocalRunPaths {
+            events: root.join("events.log"),
+            logs: root.join("logs").join("experiment.log"),
+            status: root.join("status.txt"),
+            heartbeat: root.join("heartbeat"),
+        };
+        let join = thread::spawn(move || local_worker(receiver, paths));
 
         Ok(Self {
             root,
@@ -136,7 +150,19 @@ impl LocalExperimentSession {
 
 impl ExperimentSession for LocalExperimentSession {
     fn record_event(&self, event: Event) -> Result<(), ExperimentError> {
-        self.sender()?
+        let sender = self.sender()?;
+        if let Event::Log(record) = &event {
+            sender
+                .send(LocalWrite::Log(format_log_line(record)))
+                .map_err(|_| {
+                    ExperimentError::new(
+                        ExperimentErrorKind::Internal,
+                        "Failed to queue local experiment log",
+                    )
+                })?;
+        }
+
+        sender
             .send(LocalWrite::Event(format!("{event:?}")))
             .map_err(|_| {
                 ExperimentError::new(
@@ -192,9 +218,20 @@ struct LocalWorker {
 
 enum LocalWrite {
     Event(String),
+    Log(String),
     Finish(String),
 }
 
+/// Files written by the local worker, relative to a run directory.
+struct LocalRunPaths {
+    events: PathBuf,
+    /// Human-readable copy of the run's log records, including captured process output.
+    logs: PathBuf,
+    status: PathBuf,
+    /// Refreshed every [`HEARTBEAT_INTERVAL`] while the run is active.
+    heartbeat: PathBuf,
+}
+
 struct LocalExperimentReader {
     root: PathBuf,
 }
@@ -253,22 +290,157 @@ fn append_line(path: PathBuf, line: &str) -> Result<(), std::io::Error> {
     Ok(())
 }
 
+fn format_log_line(record: &LogRecord) -> String {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let level = record.level.as_str().to_uppercase();
+    if record.attributes.is_empty() {
+        format!("{timestamp} {level} {}", record.message)
+    } else {
+        format!(
+            "{timestamp} {level} {} {}",
+            record.message,
+            Value::Object(record.attributes.clone())
+        )
+    }
+}
+
 fn local_worker(
     receiver: crossbeam::channel::Receiver<LocalWrite>,
-    events_path: PathBuf,
-    status_path: PathBuf,
+    paths: LocalRunPaths,
 ) -> Result<(), std::io::Error> {
-    while let Ok(message) = receiver.recv() {
-        match message {
-            LocalWrite::Event(line) => append_line(events_path.clone(), &line)?,
-            LocalWrite::Finish(line) => {
-                append_line(status_path, &line)?;
+    write_heartbeat(&paths.heartbeat);
+    let mut last_heartbeat = SystemTime::now();
+
+    loop {
+        match receiver.recv_timeout(HEARTBEAT_INTERVAL) {
+            Ok(LocalWrite::Event(line)) => append_line(paths.events.clone(), &line)?,
+            Ok(LocalWrite::Log(line)) => append_line(paths.logs.clone(), &line)?,
+            Ok(LocalWrite::Finish(line)) => {
+                if !write_status(&paths.status, &line)? {
+                    tracing::warn!(
+                        status = %paths.status.display(),
+                        "Local experiment run was already finalized, keeping its status"
+                    );
+                }
                 return Ok(());
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        if last_heartbeat.elapsed().unwrap_or_default() >= HEARTBEAT_INTERVAL {
+            write_heartbeat(&paths.heartbeat);
+            last_heartbeat = SystemTime::now();
         }
     }
+}
 
-    Ok(())
+/// Record that the run's process is alive, as `<pid> <timestamp>`.
+///
+/// A missed heartbeat only risks the run being seen as stale, so failures are logged rather than
+/// ending the run.
+fn write_heartbeat(path: &Path) {
+    let heartbeat = format!("{} {}", std::process::id(), chrono::Utc::now().to_rfc3339());
+    if let Err(err) = fs::write(path, heartbeat) {
+        tracing::warn!(
+            heartbeat = %path.display(),
+            "Failed to write local experiment heartbeat: {err}"
+        );
+    }
+}
+
+/// Process id recorded in a heartbeat file, if any.
+fn heartbeat_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path)
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Whether the process `pid` is still running, or `None` when this platform cannot tell.
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u32) -> Option<bool> {
+    Some(Path::new("/proc").join(pid.to_string()).exists())
+}
+
+/// Whether the process `pid` is still running, or `None` when this platform cannot tell.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_alive(pid: u32) -> Option<bool> {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .ok()
+        .map(|status| status.success())
+}
+
+/// Whether the process `pid` is still running, or `None` when this platform cannot tell.
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Write the terminal status of a run, unless it already has one.
+///
+/// Returns `false` if a status was already written, e.g. by another process marking the run as
+/// stale, so a run never gets a second terminal status.
+fn write_status(path: &Path, line: &str) -> Result<bool, std::io::Error> {
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(err) => return Err(err),
+    };
+    file.write_all(line.as_bytes())?;
+    file.write_all(b"\n")?;
+    Ok(true)
+}
+
+/// Mark unfinished runs under `root` whose process is gone as failed.
+///
+/// A run left without a status by a crashed process would otherwise look active forever. A run is
+/// considered dead once its heartbeat is older than `stale_after` and the process that wrote it is
+/// no longer running; where the process cannot be checked, the heartbeat age alone decides. Runs
+/// written before heartbeats existed have no `heartbeat` file and are left untouched.
+fn finalize_stale_runs(root: &Path, stale_after: Duration) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+
+    for run_root in entries.flatten().map(|entry| entry.path()) {
+        let status = run_root.join("status.txt");
+        let heartbeat = run_root.join("heartbeat");
+        let Ok(modified) = fs::metadata(&heartbeat).and_then(|m| m.modified()) else {
+            continue;
+        };
+        let age = modified.elapsed().unwrap_or_default();
+        if status.exists() || age < stale_after {
+            continue;
+        }
+        if heartbeat_pid(&heartbeat).and_then(process_alive) == Some(true) {
+            continue;
+        }
+
+        let completion = ExperimentCompletion::Failed(ExperimentFailure::new(
+            FailureClass::Infra,
+            format!(
+                "no heartbeat for {}s, the run's process likely crashed",
+                age.as_secs()
+            ),
+        ));
+        match write_status(&status, &format!("{completion:?}")) {
+            Ok(false) => {}
+            Ok(true) => tracing::warn!(
+                run = %run_root.display(),
+                "Marked stale local experiment run as failed"
+            ),
+            Err(err) => tracing::warn!(
+                run = %run_root.display(),
+                "Failed to mark stale local experiment run: {err}"
+            ),
+        }
+    }
 }
 
 fn collect_bundle_files(root: &Path, current: &Path) -> Result<Vec<String>, std::io::Error> {
@@ -291,6 +463,10 @@ fn collect_bundle_files(root: &Path, current: &Path) -> Result<Vec<String>, std:
     Ok(files)
 }
 
+/// Allocate the next numeric run directory under `root`.
+///
+/// Directories are claimed with `create_dir`, so concurrent runs of the same experiment, even from
+/// separate processes, each get their own directory for events, logs and artifacts.
 fn create_local_run_dir(root: &Path) -> Result<(ExperimentId, PathBuf), std::io::Error> {
     let mut next_id = 1u64;
 
@@ -319,3 +495,98 @@ fn create_local_run_dir(root: &Path) -> Result<(ExperimentId, PathBuf), std::io:
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_runs_get_distinct_run_directories() {
+        let root = tempfile::tempdir().unwrap();
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let root = root.path().to_path_buf();
+                thread::spawn(move || create_local_run_dir(&root).unwrap())
+            })
+            .collect();
+        let mut ids: Vec<String> = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap().0.as_str().to_string())
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        assert_eq!(ids.len(), 8);
+    }
+
+    #[test]
+    fn stale_runs_without_status_are_marked_failed() {
+        let root = tempfile::tempdir().unwrap();
+        let stale = root.path().join("1");
+        let finished = root.path().join("2");
+        let active = root.path().join("3");
+        let alive = root.path().join("4");
+        // No process can have this id.
+        let exited = u32::MAX;
+        for (run, heartbeat_age, pid) in [
+            (&stale, 600, exited),
+            (&finished, 600, exited),
+            (&active, 0, exited),
+            (&alive, 600, std::process::id()),
+        ] {
+            fs::create_dir(run).unwrap();
+            let mut heartbeat = fs::File::create(run.join("heartbeat")).unwrap();
+            write!(heartbeat, "{pid} 2026-01-01T00:00:00Z").unwrap();
+            heartbeat
+                .set_modified(SystemTime::now() - Duration::from_secs(heartbeat_age))
+                .unwrap();
+        }
+        fs::write(finished.join("status.txt"), "Success\n").unwrap();
+
+        finalize_stale_runs(root.path(), STALE_AFTER);
+
+        let stale_status = fs::read_to_string(stale.join("status.txt")).unwrap();
+        assert!(stale_status.starts_with("Failed("));
+        assert!(stale_status.contains("no heartbeat"));
+        assert_eq!(
+            fs::read_to_string(finished.join("status.txt")).unwrap(),
+            "Success\n"
+        );
+        assert!(!active.join("status.txt").exists());
+        assert!(!alive.join("status.txt").exists());
+    }
+
+    #[test]
+    fn a_run_is_never_given_a_second_status() {
+        let root = tempfile::tempdir().unwrap();
+        let status = root.path().join("status.txt");
+
+        assert!(write_status(&status, "Failed(stale)").unwrap());
+        assert!(!write_status(&status, "Success").unwrap());
+
+        assert_eq!(fs::read_to_string(&status).unwrap(), "Failed(stale)\n");
+    }
+
+    #[test]
+    fn runs_write_logs_under_their_own_directory() {
+        let root = tempfile::tempdir().unwrap();
+
+        let first = create_experiment_run(root.path().to_path_buf()).unwrap();
+        let second = create_experiment_run(root.path().to_path_buf()).unwrap();
+        first.log_info("from first").unwrap();
+        second.log_info("from second").unwrap();
+        let (first_id, second_id) = (first.id().clone(), second.id().clone());
+        first.finish().unwrap();
+        second.finish().unwrap();
+
+        let read_log = |id: &ExperimentId| {
+            fs::read_to_string(root.path().join(id.as_str()).join("logs/experiment.log")).unwrap()
+        };
+        let first_log = read_log(&first_id);
+        let second_log = read_log(&second_id);
+        assert!(first_log.contains("INFO from first"));
+        assert!(!first_log.contains("from second"));
+        assert!(second_log.contains("INFO from second"));
+    }
+}
@@ -1,4 +1,5 @@
 use tracel_artifact::bundle::FsBundle;
+use tracel_artifact::upload::UploadProgressSink;
 use tracel_client::Client;
 
 use tracel_experiment::{
@@ -6,18 +7,28 @@ use tracel_experiment::{
     reader::{ArtifactRef, ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact},
 };
 
+use crate::backend::cloud::CloudTransferClient;
 use crate::experiment::remote::session::{ArtifactUploadError, ArtifactUploader};
 
 use super::{ExperimentArtifactClient, ExperimentPath};
 
 pub struct CloudArtifactReader {
     client: Client,
+    transfer_client: CloudTransferClient,
     exp_path: ExperimentPath,
 }
 
 impl CloudArtifactReader {
-    pub fn new(client: Client, exp_path: ExperimentPath) -> Self {
-        Self { client, exp_path }
+    pub(crate) fn new(
+        client: Client,
+        transfer_client: CloudTransferClient,
+        exp_path: ExperimentPath,
+    ) -> Self {
+        Self {
+            client,
+            transfer_client,
+            exp_path,
+        }
     }
 }
 
@@ -36,7 +47,11 @@ impl ExperimentArtifactReader for CloudArtifactReader {
             self.exp_path.project_name().to_string(),
             num,
         );
-        let scope = ExperimentArtifactClient::new(self.client.clone(), experiment_path);
+        let scope = ExperimentArtifactClient::new(
+            self.client.clone(),
+            self.transfer_client.clone(),
+            experiment_path,
+        );
         let artifact = scope.fetch(name).map_err(|err| {
             ExperimentReaderError::with_source("Failed to resolve experiment artifact", err)
         })?;
@@ -63,9 +78,13 @@ pub struct CloudArtifactUploader {
 }
 
 impl CloudArtifactUploader {
-    pub fn new(client: Client, exp_path: ExperimentPath) -> Self {
+    pub(crate) fn new(
+        client: Client,
+        transfer_client: CloudTransferClient,
+        exp_path: ExperimentPath,
+    ) -> Self {
         Self {
-            client: ExperimentArtifactClient::new(client, exp_path),
+            client: ExperimentArtifactClient::new(client, transfer_client, exp_path),
         }
     }
 }
@@ -76,9 +95,10 @@ impl ArtifactUploader for CloudArtifactUploader {
         name: &str,
         kind: ArtifactKind,
         bundle: &FsBundle,
+        progress: &dyn UploadProgressSink,
     ) -> Result<(), ArtifactUploadError> {
         self.client
-            .upload(name, kind, bundle)
+            .upload_with_progress(name, kind, bundle, progress)
             .map(|_| ())
             .map_err(|e| ArtifactUploadError {
                 message: format!("Failed to upload artifact '{}'", name),
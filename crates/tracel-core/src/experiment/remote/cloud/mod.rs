@@ -1,14 +1,18 @@
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use tracel_client::request::{ArtifactFileSpecRequest, CreateArtifactRequest};
 use tracel_client::response::ArtifactResponse;
 use tracel_client::websocket::WebSocketError;
 use tracel_client::{Client, ClientError};
 
 use tracel_artifact::bundle::FsBundle;
-use tracel_artifact::download::{ArtifactDownloadFile, DownloadError, download_artifacts_to_sink};
+use tracel_artifact::download::{
+    ArtifactDownloadFile, DownloadError, download_artifacts_to_sink_with_client,
+};
 use tracel_artifact::upload::{
-    MultipartUploadFile, MultipartUploadPart, UploadError, upload_bundle_multipart,
+    MultipartUploadFile, MultipartUploadPart, TracingUploadProgress, UploadError, UploadPhase,
+    UploadProgressSink, upload_bundle_multipart_with_progress,
 };
 
 mod artifacts;
@@ -21,7 +25,7 @@ use tracel_experiment::{CancelToken, ExperimentId, ExperimentRun, ExperimentRunC
 
 use tracel_experiment::ExperimentProvider;
 
-use crate::backend::cloud::CloudBackend;
+use crate::backend::cloud::{CloudBackend, CloudTransferClient};
 use crate::experiment::remote::session::RemoteExperimentSession;
 
 #[derive(Debug, Clone)]
@@ -55,28 +59,61 @@ impl ExperimentPath {
     pub fn experiment_num(&self) -> i32 {
         self.experiment_num
     }
+
+    /// Link to this experiment in the web console hosted at `console_url`.
+    pub fn console_url(&self, console_url: &str) -> String {
+        format!(
+            "{}/{}/{}/experiments/{}",
+            console_url.trim_end_matches('/'),
+            self.owner_name,
+            self.project_name,
+            self.experiment_num
+        )
+    }
 }
 
 /// A scope for artifact operations within a specific experiment.
+///
+/// Artifact files go through the backend's throttled transfer client.
 #[derive(Clone)]
 pub struct ExperimentArtifactClient {
     client: Client,
+    transfer_client: CloudTransferClient,
     exp_path: ExperimentPath,
 }
 
 impl ExperimentArtifactClient {
-    pub fn new(client: Client, exp_path: ExperimentPath) -> Self {
-        Self { client, exp_path }
+    pub(crate) fn new(
+        client: Client,
+        transfer_client: CloudTransferClient,
+        exp_path: ExperimentPath,
+    ) -> Self {
+        Self {
+            client,
+            transfer_client,
+            exp_path,
+        }
     }
 
+    /// Upload `bundle` as the artifact `name`, logging its progress with `tracing`.
     pub fn upload(
         &self,
         name: impl Into<String>,
         kind: ArtifactKind,
         bundle: &FsBundle,
     ) -> Result<String, ArtifactError> {
-        let name = name.into();
+        let name: String = name.into();
+        self.upload_with_progress(&name, kind, bundle, &TracingUploadProgress)
+    }
 
+    /// Upload `bundle` as the artifact `name`, reporting its phases to `progress`.
+    pub fn upload_with_progress(
+        &self,
+        name: &str,
+        kind: ArtifactKind,
+        bundle: &FsBundle,
+        progress: &dyn UploadProgressSink,
+    ) -> Result<String, ArtifactError> {
         let mut specs = Vec::with_capacity(bundle.files().len());
         for f in bundle.files() {
             let size_bytes = f.size_bytes.ok_or_else(|| {
@@ -97,7 +134,7 @@ impl ExperimentArtifactClient {
             self.exp_path.project_name(),
             self.exp_path.experiment_num(),
             CreateArtifactRequest {
-                name: name.clone(),
+                name: name.to_string(),
                 kind: artifact_kind_name(kind).to_string(),
                 files: specs,
             },
@@ -133,8 +170,17 @@ impl ExperimentArtifactClient {
                 parts,
             });
         }
-        upload_bundle_multipart(bundle, &uploads)?;
-
+        let total_bytes = uploads
+            .iter()
+            .flat_map(|file| &file.parts)
+            .map(|part| part.size_bytes)
+            .sum();
+        progress.on_phase(name, &UploadPhase::Uploading { total_bytes });
+        upload_bundle_multipart_with_progress(&self.transfer_client, bundle, &uploads, |done| {
+            progress.on_phase(name, &UploadPhase::Progress(done))
+        })?;
+
+        progress.on_phase(name, &UploadPhase::Registering);
         self.client.complete_artifact_upload(
             self.exp_path.owner_name(),
             self.exp_path.project_name(),
@@ -142,6 +188,7 @@ impl ExperimentArtifactClient {
             &res.id,
             None,
         )?;
+        progress.on_phase(name, &UploadPhase::Finished);
 
         Ok(res.id)
     }
@@ -170,7 +217,7 @@ impl ExperimentArtifactClient {
         let mut bundle = FsBundle::temp()
             .map_err(|e| ArtifactError::Internal(format!("Failed to create temp bundle: {e}")))?;
 
-        download_artifacts_to_sink(&mut bundle, &files)?;
+        download_artifacts_to_sink_with_client(&self.transfer_client, &mut bundle, &files)?;
 
         Ok(bundle)
     }
@@ -219,6 +266,7 @@ pub enum ArtifactError {
 enum CloudError {
     Http(#[from] ClientError),
     WebSocket(#[from] WebSocketError),
+    UploadWorkers(#[from] std::io::Error),
 }
 
 impl ExperimentProvider for CloudBackend {
@@ -229,8 +277,10 @@ impl ExperimentProvider for CloudBackend {
     ) -> Result<ExperimentRun, ExperimentError> {
         create_run(
             self.client.clone(),
+            self.file_transfer_client.clone(),
             &self.namespace,
             &self.project,
+            self.console_url.as_deref(),
             name,
             attributes,
         )
@@ -244,8 +294,10 @@ impl ExperimentProvider for CloudBackend {
 
 fn create_run(
     client: Client,
+    transfer_client: CloudTransferClient,
     namespace: &str,
     project_name: &str,
+    console_url: Option<&str>,
     name: String,
     attributes: HashMap<String, Value>,
 ) -> Result<ExperimentRun, CloudError> {
@@ -254,16 +306,26 @@ fn create_run(
 
     let experiment_num = experiment.experiment_num;
     let path = ExperimentPath::new(namespace, project_name, experiment_num);
+    match console_url {
+        Some(console_url) => tracing::info!("View experiment at {}", path.console_url(console_url)),
+        None => tracing::info!("Started experiment {namespace}/{project_name} #{experiment_num}"),
+    }
     let cancel_token = CancelToken::new();
     let control = ExperimentRunControl::new(cancel_token.clone());
 
-    let artifact_uploader = CloudArtifactUploader::new(client.clone(), path.clone());
+    let artifact_uploader =
+        CloudArtifactUploader::new(client.clone(), transfer_client.clone(), path.clone());
 
     let ws = client.create_experiment_run_websocket(namespace, project_name, experiment_num)?;
 
-    let session = RemoteExperimentSession::new(Box::new(artifact_uploader), ws, control.clone());
+    let session = RemoteExperimentSession::new(
+        Box::new(artifact_uploader),
+        Arc::new(TracingUploadProgress),
+        ws,
+        control.clone(),
+    )?;
 
-    let reader = CloudArtifactReader::new(client, path);
+    let reader = CloudArtifactReader::new(client, transfer_client, path);
     let id = ExperimentId::from(format!("{}", experiment_num));
 
     Ok(ExperimentRun::new_with_control(
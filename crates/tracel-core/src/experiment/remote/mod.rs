@@ -3,3 +3,4 @@ pub mod session;
 pub mod socket;
 #[cfg(feature = "station")]
 pub mod station;
+mod upload_queue;
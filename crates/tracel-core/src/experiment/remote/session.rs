@@ -1,7 +1,9 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use tracel_experiment::error::{ExperimentError, ExperimentErrorKind};
-use tracel_experiment::session::{BundleFn, Event, ExperimentCompletion, ExperimentSession};
+use tracel_experiment::session::{
+    BundleFn, Event, ExperimentCompletion, ExperimentFailure, ExperimentSession, FailureClass,
+};
 use tracel_experiment::{
     ActivityEvent, ActivityStatus, ArtifactKind, ExperimentRunControl, LogLevel, LogRecord,
     MetricSpec, MetricValue,
@@ -9,6 +11,7 @@ use tracel_experiment::{
 
 use crossbeam::channel::Sender;
 use tracel_artifact::bundle::FsBundle;
+use tracel_artifact::upload::{UploadPhase, UploadProgressSink};
 use tracel_client::WebSocketClient;
 use tracel_client::websocket::{
     ActivityEventRequest, ActivityMeterRequest, ActivityRequest, ActivityStatusRequest,
@@ -18,6 +21,7 @@ use tracel_client::websocket::{
 
 use super::socket::ExperimentSocket;
 use super::socket::ThreadError;
+use super::upload_queue::UploadQueue;
 
 struct ActiveSession {
     sender: Sender<ExperimentMessage>,
@@ -33,34 +37,45 @@ pub struct ArtifactUploadError {
 }
 
 pub trait ArtifactUploader {
+    /// Upload `bundle` as the artifact `name`, reporting its phases to `progress`.
     fn upload(
         &self,
         name: &str,
         kind: ArtifactKind,
         bundle: &FsBundle,
+        progress: &dyn UploadProgressSink,
     ) -> Result<(), ArtifactUploadError>;
 }
 
 pub type BoxedArtifactUploader = Box<dyn ArtifactUploader + Send + Sync>;
 
+/// Experiment session streaming events to the platform over a websocket.
+///
+/// Artifacts are uploaded in the background by an [`UploadQueue`]; [`ExperimentSession::finish`]
+/// waits for pending uploads before completing the experiment. Every phase of an artifact upload,
+/// from packaging to registration, is reported to the session's [`UploadProgressSink`].
 pub struct RemoteExperimentSession {
-    artifact_uploader: BoxedArtifactUploader,
+    artifact_uploads: Mutex<Option<UploadQueue>>,
+    upload_progress: Arc<dyn UploadProgressSink>,
     active: Mutex<Option<ActiveSession>>,
 }
 
 impl RemoteExperimentSession {
     pub fn new(
         artifact_uploader: Box<dyn ArtifactUploader + Send + Sync>,
+        upload_progress: Arc<dyn UploadProgressSink>,
         websocket: WebSocketClient,
         control: ExperimentRunControl,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
+        let uploads = UploadQueue::new(Arc::from(artifact_uploader), upload_progress.clone())?;
         let (sender, receiver) = crossbeam::channel::unbounded();
         let socket = ExperimentSocket::new(websocket, receiver, control);
 
-        Self {
-            artifact_uploader,
+        Ok(Self {
+            artifact_uploads: Mutex::new(Some(uploads)),
+            upload_progress,
             active: Mutex::new(Some(ActiveSession { sender, socket })),
-        }
+        })
     }
 
     fn send(&self, message: ExperimentMessage) -> Result<(), ExperimentError> {
@@ -138,6 +153,7 @@ impl ExperimentSession for RemoteExperimentSession {
         kind: ArtifactKind,
         artifact: Box<BundleFn>,
     ) -> Result<(), ExperimentError> {
+        self.upload_progress.on_phase(name, &UploadPhase::Packaging);
         let mut bundle = FsBundle::temp().map_err(|err| {
             ExperimentError::with_source(
                 ExperimentErrorKind::Artifact,
@@ -148,15 +164,26 @@ impl ExperimentSession for RemoteExperimentSession {
 
         artifact(&mut bundle)?;
 
-        self.artifact_uploader
-            .upload(name, kind, &bundle)
-            .map_err(|err| {
-                ExperimentError::with_source(
-                    ExperimentErrorKind::Artifact,
-                    "Failed to upload experiment artifact",
-                    err,
+        // Submitting blocks while the upload workers are behind, so it must not hold the lock.
+        let submitter = self
+            .artifact_uploads
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(UploadQueue::submitter)
+            .ok_or_else(|| {
+                ExperimentError::new(
+                    ExperimentErrorKind::AlreadyFinished,
+                    "Experiment run has already finished",
                 )
-            })
+            })?;
+        submitter.submit(name, kind, bundle).map_err(|err| {
+            ExperimentError::with_source(
+                ExperimentErrorKind::Artifact,
+                "Failed to queue experiment artifact upload",
+                err,
+            )
+        })
     }
 
     fn finish(&self, completion: ExperimentCompletion) -> Result<(), ExperimentError> {
@@ -167,6 +194,36 @@ impl ExperimentSession for RemoteExperimentSession {
             )
         })?;
 
+        let failed_uploads = self
+            .artifact_uploads
+            .lock()
+            .unwrap()
+            .take()
+            .map(UploadQueue::flush)
+            .unwrap_or_default();
+        let upload_failure = (!failed_uploads.is_empty()).then(|| {
+            let failures: Vec<_> = failed_uploads
+                .iter()
+                .map(|failed| format!("{}: {}", failed.name, failed.error))
+                .collect();
+            format!(
+                "Failed to upload experiment artifacts: {}",
+                failures.join("; ")
+            )
+        });
+        // A successful run whose artifacts never arrived is recorded as failed. Failed and
+        // cancelled runs keep their status; the upload failure is added to the failure reason and
+        // returned below.
+        let completion = match (completion, &upload_failure) {
+            (ExperimentCompletion::Success, Some(reason)) => ExperimentCompletion::Failed(
+                ExperimentFailure::new(FailureClass::Infra, reason.clone()),
+            ),
+            (ExperimentCompletion::Failed(failure), Some(reason)) => ExperimentCompletion::Failed(
+                ExperimentFailure::new(failure.class, format!("{}; {reason}", failure.reason)),
+            ),
+            (completion, _) => completion,
+        };
+
         let send_result =
             active
                 .sender
@@ -185,16 +242,23 @@ impl ExperimentSession for RemoteExperimentSession {
         }
 
         match join_result {
-            Ok(_thread) => Ok(()),
+            Ok(_thread) => {}
             Err(ThreadError::WebSocket(err)) => {
                 tracing::warn!("WebSocket failure during experiment finish: {err}");
-                Ok(())
             }
-            Err(ThreadError::Panic) => Err(ExperimentError::new(
-                ExperimentErrorKind::Internal,
-                "Experiment background thread panicked",
-            )),
+            Err(ThreadError::Panic) => {
+                return Err(ExperimentError::new(
+                    ExperimentErrorKind::Internal,
+                    "Experiment background thread panicked",
+                ));
+            }
         }
+
+        if let Some(reason) = upload_failure {
+            return Err(ExperimentError::new(ExperimentErrorKind::Artifact, reason));
+        }
+
+        Ok(())
     }
 }
 
@@ -268,7 +332,10 @@ fn to_remote_activity_event(event: ActivityEvent) -> ActivityEventRequest {
 fn to_remote_completion(completion: ExperimentCompletion) -> RemoteExperimentCompletion {
     match completion {
         ExperimentCompletion::Success => RemoteExperimentCompletion::Success,
-        ExperimentCompletion::Failed(reason) => RemoteExperimentCompletion::Fail { reason },
+        // The platform only records a reason, so the class is kept as its `class: ` prefix.
+        ExperimentCompletion::Failed(failure) => RemoteExperimentCompletion::Fail {
+            reason: failure.to_string(),
+        },
         ExperimentCompletion::Cancelled => RemoteExperimentCompletion::Success,
     }
 }
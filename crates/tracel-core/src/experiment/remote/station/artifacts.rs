@@ -1,4 +1,5 @@
 use tracel_artifact::bundle::FsBundle;
+use tracel_artifact::upload::UploadProgressSink;
 use tracel_client::StationClient;
 
 use tracel_experiment::{
@@ -71,9 +72,10 @@ impl ArtifactUploader for StationArtifactUploader {
         name: &str,
         kind: ArtifactKind,
         bundle: &FsBundle,
+        progress: &dyn UploadProgressSink,
     ) -> Result<(), ArtifactUploadError> {
         self.client
-            .upload(name, kind, bundle)
+            .upload_with_progress(name, kind, bundle, progress)
             .map(|_| ())
             .map_err(|e| ArtifactUploadError {
                 message: format!("Failed to upload artifact '{}'", name),
@@ -1,8 +1,10 @@
 use std::collections::BTreeMap;
+use tracel_artifact::ReqwestTransferClient;
 use tracel_artifact::bundle::FsBundle;
 use tracel_artifact::download::{ArtifactDownloadFile, DownloadError, download_artifacts_to_sink};
 use tracel_artifact::upload::{
-    MultipartUploadFile, MultipartUploadPart, UploadError, upload_bundle_multipart,
+    MultipartUploadFile, MultipartUploadPart, TracingUploadProgress, UploadError, UploadPhase,
+    UploadProgressSink, upload_bundle_multipart_with_progress,
 };
 use tracel_client::station::experiment::{
     ArtifactFileSpecRequest, ArtifactResponse, CompleteUploadRequest, CreateArtifactRequest,
@@ -16,6 +18,7 @@ mod artifacts;
 use artifacts::{StationArtifactReader, StationArtifactUploader};
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use serde_json::Value;
 use tracel_client::station::experiment::CreateExperimentRequest;
@@ -34,6 +37,8 @@ enum StationError {
     ExperimentCreation(#[from] ClientError),
     #[error("Failed to establish WebSocket connection to Station")]
     WebSocket(#[from] WebSocketError),
+    #[error("Failed to start artifact upload workers")]
+    UploadWorkers(#[from] std::io::Error),
 }
 
 #[derive(Debug, Clone)]
@@ -63,15 +68,26 @@ impl ExperimentArtifactClient {
         Self { client, exp_path }
     }
 
+    /// Upload `bundle` as the artifact `name`, logging its progress with `tracing`.
     pub fn upload(
         &self,
         name: impl Into<String>,
         kind: ArtifactKind,
         bundle: &FsBundle,
     ) -> Result<String, ArtifactError> {
-        let client = self.client.experiments();
+        let name: String = name.into();
+        self.upload_with_progress(&name, kind, bundle, &TracingUploadProgress)
+    }
 
-        let name = name.into();
+    /// Upload `bundle` as the artifact `name`, reporting its phases to `progress`.
+    pub fn upload_with_progress(
+        &self,
+        name: &str,
+        kind: ArtifactKind,
+        bundle: &FsBundle,
+        progress: &dyn UploadProgressSink,
+    ) -> Result<String, ArtifactError> {
+        let client = self.client.experiments();
 
         let mut specs = Vec::with_capacity(bundle.files().len());
         for f in bundle.files() {
@@ -91,7 +107,7 @@ impl ExperimentArtifactClient {
         let res = client.create_artifact(
             self.exp_path.experiment_num(),
             CreateArtifactRequest {
-                name: name.clone(),
+                name: name.to_string(),
                 kind: artifact_kind_name(kind).to_string(),
                 files: specs,
             },
@@ -127,13 +143,24 @@ impl ExperimentArtifactClient {
                 parts,
             });
         }
-        upload_bundle_multipart(bundle, &uploads)?;
-
+        let total_bytes = uploads
+            .iter()
+            .flat_map(|file| &file.parts)
+            .map(|part| part.size_bytes)
+            .sum();
+        progress.on_phase(name, &UploadPhase::Uploading { total_bytes });
+        let transfer_client = ReqwestTransferClient::new();
+        upload_bundle_multipart_with_progress(&transfer_client, bundle, &uploads, |done| {
+            progress.on_phase(name, &UploadPhase::Progress(done))
+        })?;
+
+        progress.on_phase(name, &UploadPhase::Registering);
         client.complete_artifact_upload(
             self.exp_path.experiment_num(),
             &res.id,
             CompleteUploadRequest { file_names: None },
         )?;
+        progress.on_phase(name, &UploadPhase::Finished);
 
         Ok(res.id)
     }
@@ -240,7 +267,12 @@ fn create_run(
 
     let ws = experiments_client.create_run_websocket(experiment_num)?;
 
-    let session = RemoteExperimentSession::new(Box::new(artifact_uploader), ws, control.clone());
+    let session = RemoteExperimentSession::new(
+        Box::new(artifact_uploader),
+        Arc::new(TracingUploadProgress),
+        ws,
+        control.clone(),
+    )?;
 
     let reader = StationArtifactReader::new(client);
     let id = ExperimentId::from(format!("{}", experiment_num));
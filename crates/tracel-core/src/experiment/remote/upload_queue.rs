@@ -0,0 +1,268 @@
+//! Background artifact uploads for remote experiment sessions.
+//!
+//! Saving an artifact only encodes it into a temporary bundle on the training thread; the upload
+//! itself runs on a small pool of worker threads. Uploads of the same artifact name always go to
+//! the same worker, so successive versions of a checkpoint reach the server in the order they were
+//! saved. Each worker holds a bounded queue, which blocks the training thread once uploads fall too
+//! far behind instead of piling up temporary bundles on disk.
+//!
+//! Submitting goes through a cloneable [`UploadSubmitter`], so a caller holding the queue behind a
+//! lock can release it before blocking on a full worker queue.
+//!
+//! [`UploadQueue::flush`] waits for every queued upload and is called before the experiment is
+//! completed, so the run is never marked as finished while its artifacts are still in flight.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crossbeam::channel::Sender;
+use tracel_artifact::bundle::FsBundle;
+use tracel_artifact::upload::UploadProgressSink;
+use tracel_experiment::ArtifactKind;
+
+use super::session::{ArtifactUploadError, ArtifactUploader};
+
+const DEFAULT_WORKERS: usize = 2;
+const DEFAULT_QUEUE_CAPACITY: usize = 4;
+
+struct UploadJob {
+    name: String,
+    kind: ArtifactKind,
+    bundle: FsBundle,
+}
+
+/// A failed background upload, reported by [`UploadQueue::flush`].
+#[derive(Debug)]
+pub(crate) struct FailedUpload {
+    pub(crate) name: String,
+    pub(crate) error: ArtifactUploadError,
+}
+
+pub(crate) struct UploadQueue {
+    submitter: UploadSubmitter,
+    workers: Vec<JoinHandle<()>>,
+    failures: Arc<Mutex<Vec<FailedUpload>>>,
+}
+
+impl UploadQueue {
+    pub(crate) fn new(
+        uploader: Arc<dyn ArtifactUploader + Send + Sync>,
+        progress: Arc<dyn UploadProgressSink>,
+    ) -> io::Result<Self> {
+        Self::with_workers(uploader, progress, DEFAULT_WORKERS, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    pub(crate) fn with_workers(
+        uploader: Arc<dyn ArtifactUploader + Send + Sync>,
+        progress: Arc<dyn UploadProgressSink>,
+        workers: usize,
+        queue_capacity: usize,
+    ) -> io::Result<Self> {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let (senders, workers) = (0..workers.max(1))
+            .map(|index| {
+                let (sender, receiver) = crossbeam::channel::bounded::<UploadJob>(queue_capacity);
+                let uploader = uploader.clone();
+                let progress = progress.clone();
+                let failures = failures.clone();
+                let worker = thread::Builder::new()
+                    .name(format!("artifact-upload-{index}"))
+                    .spawn(move || {
+                        for job in receiver {
+                            match uploader.upload(&job.name, job.kind, &job.bundle, &*progress) {
+                                Ok(()) => {
+                                    tracing::debug!(artifact = %job.name, "Artifact uploaded")
+                                }
+                                Err(error) => {
+                                    tracing::error!(
+                                        artifact = %job.name,
+                                        "Background artifact upload failed: {error}"
+                                    );
+                                    failures.lock().unwrap().push(FailedUpload {
+                                        name: job.name,
+                                        error,
+                                    });
+                                }
+                            }
+                        }
+                    })?;
+                Ok((sender, worker))
+            })
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        Ok(Self {
+            submitter: UploadSubmitter { senders },
+            workers,
+            failures,
+        })
+    }
+
+    /// Return a handle queueing uploads to this queue's workers.
+    pub(crate) fn submitter(&self) -> UploadSubmitter {
+        self.submitter.clone()
+    }
+
+    /// Wait for every queued upload to finish and return the ones that failed.
+    ///
+    /// Blocks until every [`UploadSubmitter`] is dropped, so uploads submitted concurrently are
+    /// waited for too.
+    pub(crate) fn flush(self) -> Vec<FailedUpload> {
+        drop(self.submitter);
+        for worker in self.workers {
+            if worker.join().is_err() {
+                self.failures.lock().unwrap().push(FailedUpload {
+                    name: "<unknown>".to_string(),
+                    error: ArtifactUploadError {
+                        message: "artifact upload worker panicked".to_string(),
+                        source: None,
+                    },
+                });
+            }
+        }
+
+        std::mem::take(&mut *self.failures.lock().unwrap())
+    }
+}
+
+/// Cloneable handle queueing uploads to the workers of an [`UploadQueue`].
+#[derive(Clone)]
+pub(crate) struct UploadSubmitter {
+    senders: Vec<Sender<UploadJob>>,
+}
+
+impl UploadSubmitter {
+    /// Queue an upload, blocking while the worker assigned to `name` is at capacity.
+    pub(crate) fn submit(
+        &self,
+        name: &str,
+        kind: ArtifactKind,
+        bundle: FsBundle,
+    ) -> Result<(), ArtifactUploadError> {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        let index = (hasher.finish() % self.senders.len() as u64) as usize;
+
+        self.senders[index]
+            .send(UploadJob {
+                name: name.to_string(),
+                kind,
+                bundle,
+            })
+            .map_err(|_| ArtifactUploadError {
+                message: "artifact upload worker stopped".to_string(),
+                source: None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tracel_artifact::bundle::BundleSink;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingUploader {
+        uploads: Mutex<Vec<String>>,
+        fail: Option<&'static str>,
+    }
+
+    impl ArtifactUploader for RecordingUploader {
+        fn upload(
+            &self,
+            name: &str,
+            _kind: ArtifactKind,
+            bundle: &FsBundle,
+            _progress: &dyn UploadProgressSink,
+        ) -> Result<(), ArtifactUploadError> {
+            if self.fail == Some(name) {
+                return Err(ArtifactUploadError {
+                    message: "boom".to_string(),
+                    source: None,
+                });
+            }
+            // Slow uploads make out-of-order delivery visible if ordering were not preserved.
+            thread::sleep(Duration::from_millis(5));
+            let version = bundle.file_paths().join(",");
+            self.uploads
+                .lock()
+                .unwrap()
+                .push(format!("{name}:{version}"));
+            Ok(())
+        }
+    }
+
+    fn bundle_with(file: &str) -> FsBundle {
+        let mut bundle = FsBundle::temp().unwrap();
+        bundle.put_bytes(file, b"data").unwrap();
+        bundle
+    }
+
+    #[test]
+    fn flush_waits_for_uploads_and_keeps_per_name_order() {
+        let uploader = Arc::new(RecordingUploader::default());
+        let queue =
+            UploadQueue::with_workers(uploader.clone(), Arc::new(|_: &str, _: &_| {}), 3, 1)
+                .unwrap();
+
+        for version in 0..5 {
+            queue
+                .submitter()
+                .submit(
+                    "checkpoint",
+                    ArtifactKind::Model,
+                    bundle_with(&format!("v{version}")),
+                )
+                .unwrap();
+        }
+        queue
+            .submitter()
+            .submit("metrics", ArtifactKind::Other, bundle_with("m"))
+            .unwrap();
+
+        assert!(queue.flush().is_empty());
+
+        let uploads = uploader.uploads.lock().unwrap();
+        assert_eq!(uploads.len(), 6);
+        let checkpoints: Vec<_> = uploads
+            .iter()
+            .filter(|upload| upload.starts_with("checkpoint:"))
+            .cloned()
+            .collect();
+        assert_eq!(
+            checkpoints,
+            (0..5)
+                .map(|version| format!("checkpoint:v{version}"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn flush_reports_failed_uploads() {
+        let uploader = Arc::new(RecordingUploader {
+            fail: Some("broken"),
+            ..Default::default()
+        });
+        let queue = UploadQueue::new(uploader, Arc::new(|_: &str, _: &_| {})).unwrap();
+
+        queue
+            .submitter()
+            .submit("broken", ArtifactKind::Model, bundle_with("a"))
+            .unwrap();
+        queue
+            .submitter()
+            .submit("fine", ArtifactKind::Model, bundle_with("b"))
+            .unwrap();
+
+        let failures = queue.flush();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "broken");
+    }
+}
@@ -2,10 +2,14 @@ mod backend;
 mod connection;
 mod context;
 mod model_registry;
+mod pretrained;
 
+pub mod environment;
 pub mod experiment;
 pub mod inference;
+pub mod notification;
 
 pub use connection::{Connection, ContextError};
 pub use context::Context;
 pub use model_registry::{ModelRegistryError, ModelRegistryModule};
+pub use pretrained::{PretrainedError, PretrainedSource};
@@ -8,7 +8,7 @@ use std::path::PathBuf;
 use tracel_artifact::FileTransferClient;
 use tracel_artifact::bundle::FsBundle;
 use tracel_artifact::download::{
-    ArtifactDownloadFile, DownloadError, download_artifacts_to_sink_with_client,
+    ArtifactDownloadFile, DownloadError, download_artifacts_to_sink_resumable,
 };
 
 use crate::model_registry::ModelRegistryError;
@@ -37,6 +37,11 @@ impl ModelCache {
         self.root.join(name).join(version.to_string())
     }
 
+    /// Where interrupted downloads are kept until a later download resumes them.
+    fn partial_dir(&self) -> PathBuf {
+        self.root.join(".partial")
+    }
+
     /// Returns a bundle backed by the cached files if every expected file is already
     /// present on disk, `None` otherwise.
     fn get(&self, name: &str, version: u32, files: &[ArtifactDownloadFile]) -> Option<FsBundle> {
@@ -58,7 +63,7 @@ impl ModelCache {
 
     /// Returns the cached bundle for `name`/`version` if all `files` are already present,
     /// otherwise downloads them with `transfer_client` into a freshly reserved directory
-    /// and returns the resulting bundle.
+    /// and returns the resulting bundle. An interrupted download resumes on the next call.
     pub fn get_or_download<FTC: FileTransferClient>(
         &self,
         transfer_client: &FTC,
@@ -73,8 +78,13 @@ impl ModelCache {
         let mut bundle = self.reserve(name, version).map_err(|e| {
             ModelRegistryError::Download(Box::new(DownloadError::TargetError(e.to_string())))
         })?;
-        download_artifacts_to_sink_with_client(transfer_client, &mut bundle, files)
-            .map_err(|e| ModelRegistryError::Download(Box::new(e)))?;
+        download_artifacts_to_sink_resumable(
+            transfer_client,
+            &mut bundle,
+            files,
+            &self.partial_dir(),
+        )
+        .map_err(|e| ModelRegistryError::Download(Box::new(e)))?;
 
         Ok(bundle)
     }
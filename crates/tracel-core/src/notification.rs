@@ -0,0 +1,440 @@
+//! Webhook notifications for experiment lifecycle events.
+//!
+//! [`WebhookNotifier`] is an [`ExperimentHook`] that posts a message when a run starts, succeeds,
+//! fails, is cancelled, or reports a metric anomaly, so long runs can be followed without polling.
+//! The outcome is posted once the run is finalized. Messages are posted from a background thread,
+//! so a slow webhook never holds up the job. It can be registered explicitly with
+//! [`tracel_experiment::ExperimentModule::with_hook`], or configured per project in `tracel.toml`:
+//!
+//! ```toml
+//! [notifications]
+//! webhook = "https://hooks.slack.com/services/..."
+//! format = "slack"
+//! ```
+//!
+//! The webhook URL can also be set with the `TRACEL_WEBHOOK_URL` environment variable, which
+//! takes precedence over `tracel.toml` and keeps the URL out of version control.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracel_experiment::session::ExperimentCompletion;
+use tracel_experiment::{ExperimentHook, ExperimentRunHandle, MetricAnomaly};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+const TRACEL_WEBHOOK_URL: &str = "TRACEL_WEBHOOK_URL";
+
+/// Payload format posted by a [`WebhookNotifier`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// A JSON object with `event`, `experiment_id`, the `experiment` name and `project` when known
+    /// and, on failure, `failure_class` and `reason`.
+    #[default]
+    Json,
+    /// A Slack incoming-webhook message with a `text` field.
+    Slack,
+}
+
+/// Lifecycle event reported by a [`WebhookNotifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationEvent {
+    Started,
+    Succeeded,
+    Failed,
+    Cancelled,
+    Anomaly,
+}
+
+/// Per-project notification settings, read from the `[notifications]` table of `tracel.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct NotificationConfig {
+    pub(crate) webhook: Option<String>,
+    #[serde(default)]
+    pub(crate) format: WebhookFormat,
+}
+
+impl NotificationConfig {
+    pub(crate) fn notifier(&self) -> Option<WebhookNotifier> {
+        self.notifier_with(std::env::var(TRACEL_WEBHOOK_URL).ok())
+    }
+
+    fn notifier_with(&self, webhook_from_env: Option<String>) -> Option<WebhookNotifier> {
+        webhook_from_env
+            .filter(|url| !url.is_empty())
+            .or_else(|| self.webhook.clone())
+            .map(|url| WebhookNotifier::new(url).format(self.format))
+    }
+}
+
+/// Experiment hook that posts lifecycle events to a webhook.
+///
+/// Delivery is best effort: failures are logged and never affect the run. Messages are queued to a
+/// background thread, started with the first message and shared by clones of the notifier. When a
+/// run finishes, the job thread waits up to the webhook timeout for its outcome to be delivered, so
+/// it is not lost if the process exits right after the run.
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    format: WebhookFormat,
+    project: Option<String>,
+    http: reqwest::blocking::Client,
+    worker: Arc<OnceLock<Option<Sender<Delivery>>>>,
+}
+
+enum Delivery {
+    Post {
+        experiment_id: String,
+        payload: Value,
+    },
+    /// Acknowledged once every message queued before it was handled.
+    Flush(Sender<()>),
+}
+
+/// The run a notification is about.
+struct RunLabel<'a> {
+    project: Option<&'a str>,
+    experiment: Option<&'a str>,
+    experiment_id: &'a str,
+}
+
+impl RunLabel<'_> {
+    fn insert_into(&self, payload: &mut Value) {
+        payload["experiment_id"] = Value::from(self.experiment_id);
+        if let Some(experiment) = self.experiment {
+            payload["experiment"] = Value::from(experiment);
+        }
+        if let Some(project) = self.project {
+            payload["project"] = Value::from(project);
+        }
+    }
+}
+
+impl std::fmt::Display for RunLabel<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.project, self.experiment) {
+            (Some(project), Some(experiment)) => {
+                write!(f, "`{project}/{experiment}` #{}", self.experiment_id)
+            }
+            (None, Some(experiment)) => write!(f, "`{experiment}` #{}", self.experiment_id),
+            (_, None) => write!(f, "`{}`", self.experiment_id),
+        }
+    }
+}
+
+/// Lifecycle point a notification reports.
+#[derive(Clone, Copy)]
+enum Lifecycle<'a> {
+    Started,
+    Finished(&'a ExperimentCompletion),
+}
+
+impl Lifecycle<'_> {
+    fn event(self) -> NotificationEvent {
+        match self {
+            Lifecycle::Started => NotificationEvent::Started,
+            Lifecycle::Finished(ExperimentCompletion::Success) => NotificationEvent::Succeeded,
+            Lifecycle::Finished(ExperimentCompletion::Failed(_)) => NotificationEvent::Failed,
+            Lifecycle::Finished(ExperimentCompletion::Cancelled) => NotificationEvent::Cancelled,
+        }
+    }
+}
+
+impl WebhookNotifier {
+    /// Create a notifier posting [`WebhookFormat::Json`] payloads to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            url: url.into(),
+            format: WebhookFormat::Json,
+            project: None,
+            http,
+            worker: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Set the payload format.
+    #[must_use]
+    pub fn format(mut self, format: WebhookFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Name the project in every payload.
+    #[must_use]
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    fn label<'a>(&'a self, run: &'a ExperimentRunHandle) -> RunLabel<'a> {
+        RunLabel {
+            project: self.project.as_deref(),
+            experiment: run.experiment_name(),
+            experiment_id: run.id().as_str(),
+        }
+    }
+
+    fn worker(&self) -> Option<&Sender<Delivery>> {
+        self.worker
+            .get_or_init(|| {
+                let (sender, receiver) = crossbeam::channel::unbounded();
+                let url = self.url.clone();
+                let http = self.http.clone();
+                let spawned = std::thread::Builder::new()
+                    .name("webhook-notifier".to_string())
+                    .spawn(move || {
+                        for delivery in receiver {
+                            match delivery {
+                                Delivery::Post {
+                                    experiment_id,
+                                    payload,
+                                } => post(&http, &url, &experiment_id, payload),
+                                Delivery::Flush(done) => {
+                                    let _ = done.send(());
+                                }
+                            }
+                        }
+                    });
+                match spawned {
+                    Ok(_) => Some(sender),
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to start webhook notifier, notifications are disabled: {err}"
+                        );
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    fn post(&self, run: &ExperimentRunHandle, payload: Value) {
+        if let Some(worker) = self.worker() {
+            let _ = worker.send(Delivery::Post {
+                experiment_id: run.id().to_string(),
+                payload,
+            });
+        }
+    }
+
+    /// Wait up to [`WEBHOOK_TIMEOUT`] for queued messages to be delivered.
+    fn flush(&self) {
+        let Some(worker) = self.worker() else {
+            return;
+        };
+        let (done, delivered) = crossbeam::channel::bounded(1);
+        if worker.send(Delivery::Flush(done)).is_ok()
+            && delivered.recv_timeout(WEBHOOK_TIMEOUT).is_err()
+        {
+            tracing::warn!("Webhook notifications are still pending after {WEBHOOK_TIMEOUT:?}");
+        }
+    }
+}
+
+fn post(http: &reqwest::blocking::Client, url: &str, experiment_id: &str, payload: Value) {
+    let result = http
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload.to_string())
+        .send()
+        .and_then(|response| response.error_for_status());
+
+    if let Err(err) = result {
+        tracing::warn!(
+            experiment_id,
+            "Failed to deliver webhook notification: {err}"
+        );
+    }
+}
+
+impl ExperimentHook for WebhookNotifier {
+    fn before_run(&self, run: &ExperimentRunHandle) {
+        self.post(
+            run,
+            payload(self.format, &self.label(run), Lifecycle::Started),
+        );
+    }
+
+    fn on_finish(&self, run: &ExperimentRunHandle, completion: &ExperimentCompletion) {
+        let payload = payload(
+            self.format,
+            &self.label(run),
+            Lifecycle::Finished(completion),
+        );
+        self.post(run, payload);
+        self.flush();
+    }
+
+    fn on_anomaly(&self, run: &ExperimentRunHandle, anomaly: &MetricAnomaly) {
+        self.post(run, anomaly_payload(self.format, &self.label(run), anomaly));
+    }
+}
+
+fn payload(format: WebhookFormat, run: &RunLabel<'_>, lifecycle: Lifecycle<'_>) -> Value {
+    match format {
+        WebhookFormat::Json => {
+            let mut payload = json!({ "event": lifecycle.event() });
+            run.insert_into(&mut payload);
+            if let Lifecycle::Finished(ExperimentCompletion::Failed(failure)) = lifecycle {
+                payload["failure_class"] = Value::from(failure.class.as_str());
+                payload["reason"] = Value::from(failure.reason.as_str());
+            }
+            payload
+        }
+        WebhookFormat::Slack => {
+            let text = match lifecycle {
+                Lifecycle::Started => format!(":rocket: Experiment {run} started"),
+                Lifecycle::Finished(ExperimentCompletion::Success) => {
+                    format!(":white_check_mark: Experiment {run} succeeded")
+                }
+                Lifecycle::Finished(ExperimentCompletion::Failed(failure)) => format!(
+                    ":x: Experiment {run} failed ({}): {}",
+                    failure.class, failure.reason
+                ),
+                Lifecycle::Finished(ExperimentCompletion::Cancelled) => {
+                    format!(":no_entry_sign: Experiment {run} was cancelled")
+                }
+            };
+            json!({ "text": text })
+        }
+    }
+}
+
+fn anomaly_payload(format: WebhookFormat, run: &RunLabel<'_>, anomaly: &MetricAnomaly) -> Value {
+    match format {
+        WebhookFormat::Json => {
+            let mut payload = json!({
+                "event": NotificationEvent::Anomaly,
+                "anomaly": anomaly.kind.as_str(),
+                "metric": anomaly.metric,
+                "epoch": anomaly.epoch,
+                "reason": anomaly.to_string(),
+            });
+            run.insert_into(&mut payload);
+            payload
+        }
+        WebhookFormat::Slack => json!({
+            "text": format!(":warning: Experiment {run} anomaly: {anomaly}")
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracel_experiment::AnomalyKind;
+    use tracel_experiment::session::{ExperimentFailure, FailureClass};
+
+    use super::*;
+
+    const RUN: RunLabel<'static> = RunLabel {
+        project: Some("vision"),
+        experiment: Some("train"),
+        experiment_id: "42",
+    };
+
+    const UNNAMED_RUN: RunLabel<'static> = RunLabel {
+        project: None,
+        experiment: None,
+        experiment_id: "42",
+    };
+
+    #[test]
+    fn json_payload_includes_failure_details() {
+        let completion = ExperimentCompletion::Failed(ExperimentFailure::new(
+            FailureClass::OutOfMemory,
+            "wgpu: out of memory",
+        ));
+
+        let payload = payload(WebhookFormat::Json, &RUN, Lifecycle::Finished(&completion));
+
+        assert_eq!(
+            payload,
+            json!({
+                "event": "failed",
+                "experiment_id": "42",
+                "experiment": "train",
+                "project": "vision",
+                "failure_class": "out_of_memory",
+                "reason": "wgpu: out of memory",
+            })
+        );
+    }
+
+    #[test]
+    fn slack_payload_is_a_text_message() {
+        let succeeded = Lifecycle::Finished(&ExperimentCompletion::Success);
+
+        assert_eq!(
+            payload(WebhookFormat::Slack, &RUN, succeeded),
+            json!({ "text": ":white_check_mark: Experiment `vision/train` #42 succeeded" })
+        );
+        assert_eq!(
+            payload(WebhookFormat::Slack, &UNNAMED_RUN, succeeded),
+            json!({ "text": ":white_check_mark: Experiment `42` succeeded" })
+        );
+    }
+
+    #[test]
+    fn anomaly_payload_describes_the_metric() {
+        let anomaly = MetricAnomaly {
+            metric: "loss".to_string(),
+            epoch: 3,
+            split: "train".to_string(),
+            iteration: 120,
+            value: f64::NAN,
+            kind: AnomalyKind::NotFinite,
+        };
+
+        assert_eq!(
+            anomaly_payload(WebhookFormat::Json, &RUN, &anomaly),
+            json!({
+                "event": "anomaly",
+                "experiment_id": "42",
+                "experiment": "train",
+                "project": "vision",
+                "anomaly": "not_finite",
+                "metric": "loss",
+                "epoch": 3,
+                "reason": "loss is NaN at epoch 3 (train)",
+            })
+        );
+        assert_eq!(
+            anomaly_payload(WebhookFormat::Slack, &UNNAMED_RUN, &anomaly),
+            json!({ "text": ":warning: Experiment `42` anomaly: loss is NaN at epoch 3 (train)" })
+        );
+    }
+
+    #[test]
+    fn config_without_webhook_has_no_notifier() {
+        let config: NotificationConfig = toml::from_str("format = \"slack\"").unwrap();
+        assert!(config.notifier_with(None).is_none());
+
+        let config: NotificationConfig =
+            toml::from_str("webhook = \"https://example.com/hook\"\nformat = \"slack\"").unwrap();
+        let notifier = config.notifier_with(None).unwrap();
+        assert_eq!(notifier.format, WebhookFormat::Slack);
+        assert_eq!(notifier.url, "https://example.com/hook");
+    }
+
+    #[test]
+    fn webhook_url_can_come_from_the_environment() {
+        let config: NotificationConfig = toml::from_str("format = \"slack\"").unwrap();
+
+        let notifier = config
+            .notifier_with(Some("https://example.com/env".to_string()))
+            .unwrap();
+
+        assert_eq!(notifier.url, "https://example.com/env");
+        assert_eq!(notifier.format, WebhookFormat::Slack);
+        assert!(config.notifier_with(Some(String::new())).is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! Initialization of a job's model from a prior experiment or a registry model version.
+//!
+//! A [`PretrainedSource`] names where the starting weights come from. It parses from the strings
+//! accepted by `--init-from`, and serializes to and from the same strings so it can be a field of a
+//! job config:
+//!
+//! - `experiment:<experiment_id>:<artifact>` loads an artifact saved by a previous experiment.
+//! - `model:<name>:<version>` loads a version of a model from the registry.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct FineTuneConfig {
+//!     init_from: Option<PretrainedSource>,
+//! }
+//!
+//! fn fine_tune(run: &ExperimentRun, config: FineTuneConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
+//!     let record: ModelRecord = match &config.init_from {
+//!         Some(source) => context
+//!             .load_pretrained(run, source, &Default::default())
+//!             .map_err(|e| e.to_string())?,
+//!         None => ModelRecord::default(),
+//!     };
+//!     // ...
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracel_artifact::bundle::BundleDecode;
+use tracel_experiment::error::ExperimentError;
+use tracel_experiment::{ExperimentId, ExperimentRun, LogRecord};
+
+use crate::model_registry::{ModelRegistryError, ModelRegistryModule};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PretrainedError {
+    #[error(
+        "invalid pretrained source '{0}': expected 'experiment:<id>:<artifact>' or 'model:<name>:<version>'"
+    )]
+    InvalidSource(String),
+    #[error("no model registry is available for this connection")]
+    RegistryUnavailable,
+    #[error("failed to load pretrained artifact: {0}")]
+    Experiment(#[from] ExperimentError),
+    #[error("failed to load pretrained model: {0}")]
+    ModelRegistry(#[from] ModelRegistryError),
+}
+
+/// Where a job's pretrained weights are loaded from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PretrainedSource {
+    /// An artifact saved by a previous experiment.
+    Experiment {
+        experiment_id: ExperimentId,
+        artifact: String,
+    },
+    /// A version of a model from the registry.
+    Model { name: String, version: u32 },
+}
+
+impl PretrainedSource {
+    /// Load and decode the pretrained weights, recording where they came from on `run`.
+    ///
+    /// Experiment artifacts are loaded through [`ExperimentRun::use_artifact`], which records the
+    /// lineage edge. Registry models are recorded as an `init_from` log on the run.
+    pub(crate) fn load<D: BundleDecode>(
+        &self,
+        run: &ExperimentRun,
+        models: Option<ModelRegistryModule>,
+        settings: &D::Settings,
+    ) -> Result<D, PretrainedError> {
+        match self {
+            PretrainedSource::Experiment {
+                experiment_id,
+                artifact,
+            } => Ok(run.use_artifact(experiment_id.clone(), artifact, settings)?),
+            PretrainedSource::Model { name, version } => {
+                let models = models.ok_or(PretrainedError::RegistryUnavailable)?;
+                let decoded = models.load(name, *version, settings)?;
+                run.log(
+                    LogRecord::info(format!("Initialized from {self}"))
+                        .with("init_from", self.to_string()),
+                )?;
+                Ok(decoded)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PretrainedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PretrainedSource::Experiment {
+                experiment_id,
+                artifact,
+            } => write!(f, "experiment:{experiment_id}:{artifact}"),
+            PretrainedSource::Model { name, version } => write!(f, "model:{name}:{version}"),
+        }
+    }
+}
+
+impl FromStr for PretrainedSource {
+    type Err = PretrainedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PretrainedError::InvalidSource(s.to_string());
+        let mut parts = s.splitn(3, ':');
+        let (Some(kind), Some(id), Some(rest)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        if id.is_empty() || rest.is_empty() {
+            return Err(invalid());
+        }
+
+        match kind {
+            "experiment" => Ok(PretrainedSource::Experiment {
+                experiment_id: ExperimentId::from(id),
+                artifact: rest.to_string(),
+            }),
+            "model" => Ok(PretrainedSource::Model {
+                name: id.to_string(),
+                version: rest.parse().map_err(|_| invalid())?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for PretrainedSource {
+    type Error = PretrainedError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PretrainedSource> for String {
+    fn from(value: PretrainedSource) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_experiment_source_when_parsing_then_keeps_the_artifact_name() {
+        let source: PretrainedSource = "experiment:42:model".parse().unwrap();
+
+        assert_eq!(
+            source,
+            PretrainedSource::Experiment {
+                experiment_id: ExperimentId::from("42"),
+                artifact: "model".to_string(),
+            }
+        );
+        assert_eq!(source.to_string(), "experiment:42:model");
+    }
+
+    #[test]
+    fn given_model_source_when_deserializing_then_parses_the_version() {
+        let source: PretrainedSource = serde_json::from_str("\"model:mnist:3\"").unwrap();
+
+        assert_eq!(
+            source,
+            PretrainedSource::Model {
+                name: "mnist".to_string(),
+                version: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn given_source_when_serializing_then_round_trips_through_the_same_string() {
+        let sources = [
+            PretrainedSource::Experiment {
+                experiment_id: ExperimentId::from("42"),
+                artifact: "checkpoint:best".to_string(),
+            },
+            PretrainedSource::Model {
+                name: "mnist".to_string(),
+                version: 3,
+            },
+        ];
+
+        for source in sources {
+            let json = serde_json::to_string(&source).unwrap();
+            assert_eq!(json, format!("\"{source}\""));
+            assert_eq!(
+                serde_json::from_str::<PretrainedSource>(&json).unwrap(),
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn given_malformed_source_when_parsing_then_returns_invalid_source() {
+        for raw in ["mnist", "model:mnist", "model:mnist:latest", "run:1:model"] {
+            assert!(
+                matches!(raw.parse::<PretrainedSource>(), Err(PretrainedError::InvalidSource(s)) if s == raw),
+                "{raw} should be rejected"
+            );
+        }
+    }
+}
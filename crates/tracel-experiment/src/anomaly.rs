@@ -0,0 +1,353 @@
+//! Detection of diverging runs from their logged metrics.
+//!
+//! [`AnomalyDetector`] is an opt-in [`ExperimentHook`] that watches loss metrics as they are
+//! logged. A value that is NaN or infinite, or that grows past a configurable multiple of the
+//! lowest value seen so far, is reported as a [`MetricAnomaly`]:
+//!
+//! - a warning flagged with an `anomaly` attribute is logged to the run,
+//! - every hook of the run is notified through [`ExperimentHook::on_anomaly`], which is how
+//!   webhook notifiers learn about it,
+//! - if configured, the run is cancelled so the job can stop early.
+//!
+//! Only the first anomaly of a run is reported, since a diverged loss usually stays diverged.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::session::ExperimentCompletion;
+use crate::{ExperimentHook, ExperimentRunHandle, LogRecord, MetricValue, RunKey};
+
+/// What made a metric value anomalous.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnomalyKind {
+    /// The value was NaN or infinite.
+    NotFinite,
+    /// The value exceeded `factor` times the lowest value seen for the metric.
+    Exploded { lowest: f64, factor: f64 },
+}
+
+impl AnomalyKind {
+    /// Stable identifier, recorded as the `anomaly` log attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyKind::NotFinite => "not_finite",
+            AnomalyKind::Exploded { .. } => "exploded",
+        }
+    }
+}
+
+/// A metric value flagged by an [`AnomalyDetector`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricAnomaly {
+    pub metric: String,
+    pub epoch: usize,
+    pub split: String,
+    pub iteration: usize,
+    pub value: f64,
+    pub kind: AnomalyKind,
+}
+
+impl fmt::Display for MetricAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            metric,
+            epoch,
+            split,
+            value,
+            ..
+        } = self;
+        match self.kind {
+            AnomalyKind::NotFinite => write!(f, "{metric} is {value} at epoch {epoch} ({split})"),
+            AnomalyKind::Exploded { lowest, .. } => write!(
+                f,
+                "{metric} exploded to {value} at epoch {epoch} ({split}), lowest was {lowest}"
+            ),
+        }
+    }
+}
+
+/// Experiment hook flagging NaN, infinite or exploding loss values.
+///
+/// By default every metric whose name contains `loss` is watched, and only non-finite values are
+/// anomalies. See the [module documentation](self) for how anomalies are reported.
+#[derive(Clone, Default)]
+pub struct AnomalyDetector {
+    metrics: Vec<String>,
+    explosion_factor: Option<f64>,
+    cancel_run: bool,
+    /// State per run, keyed by experiment name and id since one hook can observe several jobs.
+    runs: Arc<Mutex<HashMap<RunKey, RunState>>>,
+}
+
+#[derive(Default)]
+struct RunState {
+    lowest: HashMap<String, f64>,
+    reported: bool,
+}
+
+impl AnomalyDetector {
+    /// Create a detector watching `loss` metrics for non-finite values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch the metric `name` instead of the default `loss` metrics. Can be called repeatedly.
+    #[must_use]
+    pub fn watch(mut self, name: impl Into<String>) -> Self {
+        self.metrics.push(name.into());
+        self
+    }
+
+    /// Also flag positive values above `factor` times the lowest value seen for the metric.
+    #[must_use]
+    pub fn explosion_factor(mut self, factor: f64) -> Self {
+        self.explosion_factor = Some(factor);
+        self
+    }
+
+    /// Cancel the run when an anomaly is detected, so the job can stop early.
+    ///
+    /// The job observes the cancellation through [`crate::ExperimentRun::cancel_token`], and the
+    /// run is finalized as cancelled.
+    #[must_use]
+    pub fn cancel_run(mut self) -> Self {
+        self.cancel_run = true;
+        self
+    }
+
+    fn is_watched(&self, name: &str) -> bool {
+        if self.metrics.is_empty() {
+            name.contains("loss")
+        } else {
+            self.metrics.iter().any(|metric| metric == name)
+        }
+    }
+
+    fn check(
+        &self,
+        state: &mut RunState,
+        epoch: usize,
+        split: &str,
+        iteration: usize,
+        items: &[MetricValue],
+    ) -> Option<MetricAnomaly> {
+        if state.reported {
+            return None;
+        }
+
+        for item in items.iter().filter(|item| self.is_watched(&item.name)) {
+            let value = item.value;
+            let lowest = state.lowest.get(&item.name).copied();
+            let kind = if !value.is_finite() {
+                Some(AnomalyKind::NotFinite)
+            } else {
+                match (lowest, self.explosion_factor) {
+                    (Some(lowest), Some(factor)) if lowest > 0.0 && value > lowest * factor => {
+                        Some(AnomalyKind::Exploded { lowest, factor })
+                    }
+                    _ => None,
+                }
+            };
+
+            if let Some(kind) = kind {
+                state.reported = true;
+                return Some(MetricAnomaly {
+                    metric: item.name.clone(),
+                    epoch,
+                    split: split.to_string(),
+                    iteration,
+                    value,
+                    kind,
+                });
+            }
+            state.lowest.insert(
+                item.name.clone(),
+                lowest.map_or(value, |lowest| lowest.min(value)),
+            );
+        }
+        None
+    }
+
+    fn report(&self, run: &ExperimentRunHandle, anomaly: &MetricAnomaly) {
+        let record = LogRecord::warn(format!("Metric anomaly: {anomaly}"))
+            .with("anomaly", anomaly.kind.as_str())
+            .with("metric", anomaly.metric.clone())
+            .with("epoch", anomaly.epoch);
+        if let Err(err) = run.log(record) {
+            tracing::warn!(experiment_id = %run.id(), "Failed to log metric anomaly: {err}");
+        }
+
+        run.notify_anomaly(anomaly);
+        if self.cancel_run {
+            run.cancel_token().cancel();
+        }
+    }
+}
+
+impl ExperimentHook for AnomalyDetector {
+    fn on_finish(&self, run: &ExperimentRunHandle, _completion: &ExperimentCompletion) {
+        self.runs.lock().unwrap().remove(&run.run_key());
+    }
+
+    fn on_metric(
+        &self,
+        run: &ExperimentRunHandle,
+        epoch: usize,
+        split: &str,
+        iteration: usize,
+        items: &[MetricValue],
+    ) {
+        let anomaly = {
+            let mut runs = self.runs.lock().unwrap();
+            let state = runs.entry(run.run_key()).or_default();
+            self.check(state, epoch, split, iteration, items)
+        };
+        if let Some(anomaly) = anomaly {
+            self.report(run, &anomaly);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ExperimentError;
+    use crate::reader::{ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact};
+    use crate::session::{BundleFn, Event, ExperimentSession};
+    use crate::{ArtifactKind, CancelToken, ExperimentId, ExperimentRun, ExperimentRunHandleExt};
+
+    struct MockSession;
+
+    impl ExperimentSession for MockSession {
+        fn record_event(&self, _event: Event) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn save_artifact(
+            &self,
+            _name: &str,
+            _kind: ArtifactKind,
+            _artifact: Box<BundleFn>,
+        ) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn finish(&self, _completion: ExperimentCompletion) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+    }
+
+    struct NoopExperimentDataReader;
+
+    impl ExperimentArtifactReader for NoopExperimentDataReader {
+        fn load_artifact_raw(
+            &self,
+            _experiment_id: ExperimentId,
+            _name: &str,
+        ) -> Result<LoadedArtifact, ExperimentReaderError> {
+            Err(ExperimentReaderError::new("Artifact not found"))
+        }
+    }
+
+    fn create_run(experiment: &str) -> ExperimentRun {
+        ExperimentRun::new(
+            "1",
+            MockSession,
+            NoopExperimentDataReader,
+            CancelToken::default(),
+        )
+        .with_experiment_name(experiment)
+    }
+
+    fn values(name: &str, values: &[f64]) -> Vec<Vec<MetricValue>> {
+        values
+            .iter()
+            .map(|value| {
+                vec![MetricValue {
+                    name: name.to_string(),
+                    value: *value,
+                }]
+            })
+            .collect()
+    }
+
+    fn first_anomaly(detector: &AnomalyDetector, batches: &[Vec<MetricValue>]) -> Option<usize> {
+        let mut state = RunState::default();
+        batches
+            .iter()
+            .position(|items| detector.check(&mut state, 1, "train", 0, items).is_some())
+    }
+
+    #[test]
+    fn non_finite_loss_is_an_anomaly() {
+        let detector = AnomalyDetector::new();
+
+        assert_eq!(
+            first_anomaly(&detector, &values("loss", &[1.0, f64::NAN])),
+            Some(1)
+        );
+        assert_eq!(
+            first_anomaly(&detector, &values("loss", &[f64::INFINITY])),
+            Some(0)
+        );
+        assert_eq!(
+            first_anomaly(&detector, &values("accuracy", &[f64::NAN])),
+            None
+        );
+    }
+
+    #[test]
+    fn explosion_is_relative_to_the_lowest_value() {
+        let detector = AnomalyDetector::new().explosion_factor(10.0);
+        let batches = values("val_loss", &[2.0, 0.5, 4.0, 5.5]);
+
+        let mut state = RunState::default();
+        let anomalies: Vec<_> = batches
+            .iter()
+            .filter_map(|items| detector.check(&mut state, 1, "valid", 0, items))
+            .collect();
+
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].value, 5.5);
+        assert_eq!(
+            anomalies[0].kind,
+            AnomalyKind::Exploded {
+                lowest: 0.5,
+                factor: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn watched_metrics_replace_the_default() {
+        let detector = AnomalyDetector::new().watch("reward");
+
+        assert_eq!(first_anomaly(&detector, &values("loss", &[f64::NAN])), None);
+        assert_eq!(
+            first_anomaly(&detector, &values("reward", &[f64::NAN])),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn run_state_is_kept_per_experiment_and_dropped_when_the_run_finishes() {
+        let detector = AnomalyDetector::new().explosion_factor(10.0);
+        let train = create_run("train").handle();
+        let evaluate = create_run("evaluate").handle();
+        let metric = |value| {
+            vec![MetricValue {
+                name: "loss".to_string(),
+                value,
+            }]
+        };
+
+        detector.on_metric(&train, 1, "train", 0, &metric(0.1));
+        detector.on_metric(&evaluate, 1, "valid", 0, &metric(5.0));
+        assert_eq!(detector.runs.lock().unwrap().len(), 2);
+
+        detector.on_finish(&train, &ExperimentCompletion::Cancelled);
+        detector.on_finish(&evaluate, &ExperimentCompletion::Success);
+        assert!(detector.runs.lock().unwrap().is_empty());
+    }
+}
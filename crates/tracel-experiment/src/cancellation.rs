@@ -1,6 +1,8 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use crate::error::{ExperimentError, ExperimentErrorKind};
+
 /// A task or object that can participate in experiment cancellation propagation.
 ///
 /// Implementations should make [`Self::cancel`] idempotent and thread-safe.
@@ -65,6 +67,30 @@ impl CancelToken {
         self.inner.cancelled.load(Ordering::Acquire)
     }
 
+    /// Return a [`ExperimentErrorKind::Cancelled`] error once this token has been cancelled.
+    ///
+    /// Meant for long-running loops inside a job, such as environment rollouts or data
+    /// generation, so they can stop at a safe point with `?`:
+    ///
+    /// ```ignore
+    /// let token = run.cancel_token();
+    /// for episode in 0..episodes {
+    ///     token.check()?;
+    ///     play(episode);
+    /// }
+    /// ```
+    ///
+    /// The run of a job returning this error is finalized as cancelled rather than failed.
+    pub fn check(&self) -> Result<(), ExperimentError> {
+        if self.is_cancelled() {
+            return Err(ExperimentError::new(
+                ExperimentErrorKind::Cancelled,
+                "Experiment was cancelled",
+            ));
+        }
+        Ok(())
+    }
+
     /// Link a child so it is cancelled when this token is cancelled.
     ///
     /// If this token is already cancelled, the child is cancelled immediately.
@@ -181,6 +207,17 @@ mod tests {
         assert!(token.is_cancelled());
     }
 
+    #[test]
+    fn test_check_reports_cancellation() {
+        let token = CancelToken::new();
+        assert!(token.check().is_ok());
+
+        token.cancel();
+
+        let err = token.check().unwrap_err();
+        assert_eq!(err.kind, ExperimentErrorKind::Cancelled);
+    }
+
     #[test]
     fn test_cancel_children() {
         let token = CancelToken::new();
@@ -0,0 +1,44 @@
+//! Lifecycle hooks for wrapping experiment jobs.
+
+use crate::session::{ExperimentCompletion, ExperimentFailure};
+use crate::{ExperimentRunHandle, MetricAnomaly, MetricValue};
+
+/// Callbacks invoked around an experiment job's lifecycle.
+///
+/// Hooks let integrations such as profilers or notifiers observe a job without changes to its
+/// training code. Register them with [`crate::ExperimentModule::with_hook`] or
+/// [`crate::ExperimentJob::hook`]; they run on the thread driving the job, in registration order.
+///
+/// Every method has an empty default implementation, so a hook only overrides what it needs.
+pub trait ExperimentHook: Send + Sync + 'static {
+    /// Called after the experiment is created, before the job function runs.
+    fn before_run(&self, _run: &ExperimentRunHandle) {}
+
+    /// Called after the job function returned successfully, before the run is finished.
+    fn after_run(&self, _run: &ExperimentRunHandle) {}
+
+    /// Called when the job function failed or panicked, before the run is marked as failed.
+    fn on_error(&self, _run: &ExperimentRunHandle, _failure: &ExperimentFailure) {}
+
+    /// Called once the run is finalized, with the completion the backend recorded.
+    ///
+    /// A successful run whose finalization failed is reported as an [`FailureClass::Infra`]
+    /// failure.
+    ///
+    /// [`FailureClass::Infra`]: crate::session::FailureClass::Infra
+    fn on_finish(&self, _run: &ExperimentRunHandle, _completion: &ExperimentCompletion) {}
+
+    /// Called for every batch of metric values logged by the run.
+    fn on_metric(
+        &self,
+        _run: &ExperimentRunHandle,
+        _epoch: usize,
+        _split: &str,
+        _iteration: usize,
+        _items: &[MetricValue],
+    ) {
+    }
+
+    /// Called when a metric anomaly is detected for the run, see [`crate::AnomalyDetector`].
+    fn on_anomaly(&self, _run: &ExperimentRunHandle, _anomaly: &MetricAnomaly) {}
+}
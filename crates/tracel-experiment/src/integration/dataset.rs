@@ -0,0 +1,245 @@
+//! Reproducible train/validation/test splits of Burn datasets.
+//!
+//! [`SplitConfig`] is meant to be part of a job's input, next to the other runtime parameters.
+//! Splitting shuffles the dataset indices with the configured seed, so the same configuration
+//! always yields the same splits, and records a [`SplitMetadata`] config on the run so the split
+//! can be reproduced later:
+//!
+//! ```ignore
+//! use tracel_experiment::integration::dataset::SplitConfig;
+//!
+//! #[derive(Deserialize)]
+//! struct TrainArgs {
+//!     split: SplitConfig,
+//! }
+//!
+//! fn train(run: &ExperimentRun, args: TrainArgs) -> Result<(), Box<dyn Error + Send + Sync>> {
+//!     let splits = args.split.split(run, MnistDataset::train())?;
+//!     // Use `splits.train`, `splits.valid` and `splits.test` as datasets.
+//! }
+//! ```
+
+use burn::data::dataset::Dataset;
+use burn::data::dataset::transform::SelectionDataset;
+use serde::{Deserialize, Serialize};
+
+use crate::ExperimentRunHandle;
+use crate::error::{ExperimentError, ExperimentErrorKind};
+
+/// Name of the config the split metadata is logged under.
+pub const SPLIT_CONFIG_NAME: &str = "dataset_split";
+
+/// Seed and ratios of a train/validation/test split.
+///
+/// Ratios are relative to each other and do not need to sum to one. Missing fields take their
+/// default value: seed `42` and an `0.8`/`0.1`/`0.1` split.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitConfig {
+    pub seed: u64,
+    pub train: f64,
+    pub valid: f64,
+    pub test: f64,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            train: 0.8,
+            valid: 0.1,
+            test: 0.1,
+        }
+    }
+}
+
+/// Number of items in each split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitSizes {
+    pub train: usize,
+    pub valid: usize,
+    pub test: usize,
+}
+
+/// What a split was made from, logged on the run as the [`SPLIT_CONFIG_NAME`] config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitMetadata {
+    #[serde(flatten)]
+    pub config: SplitConfig,
+    /// Number of items in the dataset before splitting.
+    pub dataset_len: usize,
+    pub sizes: SplitSizes,
+}
+
+/// The three datasets produced by [`SplitConfig::split`], sharing the original dataset.
+pub struct DatasetSplits<D, I>
+where
+    D: Dataset<I>,
+    I: Clone + Send + Sync,
+{
+    pub train: SelectionDataset<D, I>,
+    pub valid: SelectionDataset<D, I>,
+    pub test: SelectionDataset<D, I>,
+    pub metadata: SplitMetadata,
+}
+
+impl SplitConfig {
+    /// Create the default split with another seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Self::default()
+        }
+    }
+
+    /// Use the given train, validation and test ratios.
+    #[must_use]
+    pub fn ratios(mut self, train: f64, valid: f64, test: f64) -> Self {
+        self.train = train;
+        self.valid = valid;
+        self.test = test;
+        self
+    }
+
+    /// Number of items each split gets out of `len`.
+    ///
+    /// The validation and test sizes are rounded down, and the train split takes the remainder.
+    pub fn sizes(&self, len: usize) -> Result<SplitSizes, ExperimentError> {
+        let ratios = [self.train, self.valid, self.test];
+        if ratios
+            .iter()
+            .any(|ratio| !ratio.is_finite() || *ratio < 0.0)
+            || self.train <= 0.0
+        {
+            return Err(ExperimentError::new(
+                ExperimentErrorKind::Internal,
+                format!(
+                    "Invalid split ratios {}/{}/{}: ratios must be finite and non-negative, \
+                     with a positive train ratio",
+                    self.train, self.valid, self.test
+                ),
+            ));
+        }
+
+        let total: f64 = ratios.iter().sum();
+        let share = |ratio: f64| (len as f64 * ratio / total).floor() as usize;
+        let valid = share(self.valid);
+        let test = share(self.test);
+        Ok(SplitSizes {
+            train: len - valid - test,
+            valid,
+            test,
+        })
+    }
+
+    /// Split `dataset` and record the split metadata on `run`.
+    pub fn split<D, I>(
+        &self,
+        run: impl Into<ExperimentRunHandle>,
+        dataset: D,
+    ) -> Result<DatasetSplits<D, I>, ExperimentError>
+    where
+        D: Dataset<I>,
+        I: Clone + Send + Sync,
+    {
+        let splits = self.split_dataset(dataset)?;
+        run.into().log_config(SPLIT_CONFIG_NAME, &splits.metadata)?;
+        Ok(splits)
+    }
+
+    /// Split `dataset` without recording anything.
+    pub fn split_dataset<D, I>(&self, dataset: D) -> Result<DatasetSplits<D, I>, ExperimentError>
+    where
+        D: Dataset<I>,
+        I: Clone + Send + Sync,
+    {
+        let dataset_len = dataset.len();
+        let sizes = self.sizes(dataset_len)?;
+        let shuffled = SelectionDataset::new_shuffled(dataset, self.seed);
+
+        let valid_end = sizes.train + sizes.valid;
+        Ok(DatasetSplits {
+            train: shuffled.slice(0, sizes.train),
+            valid: shuffled.slice(sizes.train, valid_end),
+            test: shuffled.slice(valid_end, dataset_len),
+            metadata: SplitMetadata {
+                config: *self,
+                dataset_len,
+                sizes,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::data::dataset::InMemDataset;
+
+    use super::*;
+
+    fn items<I: Clone + Send + Sync>(dataset: &SelectionDataset<InMemDataset<I>, I>) -> Vec<I> {
+        dataset.iter().collect()
+    }
+
+    #[test]
+    fn sizes_round_the_smaller_splits_down() {
+        let sizes = SplitConfig::default().sizes(105).unwrap();
+
+        assert_eq!(
+            sizes,
+            SplitSizes {
+                train: 85,
+                valid: 10,
+                test: 10,
+            }
+        );
+
+        let sizes = SplitConfig::default()
+            .ratios(3.0, 1.0, 0.0)
+            .sizes(10)
+            .unwrap();
+        assert_eq!((sizes.train, sizes.valid, sizes.test), (8, 2, 0));
+    }
+
+    #[test]
+    fn invalid_ratios_are_rejected() {
+        for (train, valid, test) in [(0.0, 0.5, 0.5), (0.8, -0.1, 0.3), (f64::NAN, 0.1, 0.1)] {
+            let config = SplitConfig::default().ratios(train, valid, test);
+            assert!(config.sizes(10).is_err(), "{train}/{valid}/{test}");
+        }
+    }
+
+    #[test]
+    fn splits_are_disjoint_and_reproducible() {
+        let dataset = || InMemDataset::new((0..50).collect::<Vec<u32>>());
+        let config = SplitConfig::new(7);
+
+        let first = config.split_dataset(dataset()).unwrap();
+        let second = config.split_dataset(dataset()).unwrap();
+        let other_seed = SplitConfig::new(8).split_dataset(dataset()).unwrap();
+
+        assert_eq!(items(&first.train), items(&second.train));
+        assert_eq!(items(&first.test), items(&second.test));
+        assert_ne!(items(&first.train), items(&other_seed.train));
+
+        let mut all = [items(&first.train), items(&first.valid), items(&first.test)].concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..50).collect::<Vec<_>>());
+        assert_eq!(first.metadata.sizes.train, first.train.len());
+    }
+
+    #[test]
+    fn metadata_serializes_flat() {
+        let metadata = SplitMetadata {
+            config: SplitConfig::new(1),
+            dataset_len: 10,
+            sizes: SplitConfig::new(1).sizes(10).unwrap(),
+        };
+
+        let value = serde_json::to_value(&metadata).unwrap();
+
+        assert_eq!(value["seed"], 1);
+        assert_eq!(value["train"], 0.8);
+        assert_eq!(value["sizes"]["valid"], 1);
+    }
+}
@@ -3,7 +3,14 @@
 //! Use [`training`] for Burn `train` integration points such as metric logging, checkpoint
 //! recording, and cancellation-aware learner interruption.
 //!
+//! Use [`dataset`] to split Burn datasets into reproducible train/validation/test sets.
+//!
 //! Use [`tracing`] to route `tracing` events into the current experiment.
+//!
+//! Use [`output`] to forward the stdout/stderr of a spawned process, or of the current one, into
+//! experiment logs.
 
+pub mod dataset;
+pub mod output;
 pub mod tracing;
 pub mod training;
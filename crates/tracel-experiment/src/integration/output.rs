@@ -0,0 +1,652 @@
+//! Forwarding of process output into experiment logs.
+//!
+//! Training code often reports progress through `println!` or a stdout/stderr logger rather than
+//! through the experiment API. [`OutputCapture`] reads such a stream line by line and forwards it
+//! to the experiment as [`LogRecord`]s, so the output shows up next to the run instead of only in
+//! the terminal that launched it.
+//!
+//! Lines are grouped into chunks before being sent: a chunk is flushed once it reaches
+//! [`OutputCapture::max_chunk_bytes`] or once its oldest line has been buffered for
+//! [`OutputCapture::flush_interval`], whichever comes first, even if the stream goes quiet. This
+//! keeps chatty programs from emitting one log event per line.
+//!
+//! A spawned process's piped output is forwarded with [`OutputCapture::attach`]:
+//!
+//! ```ignore
+//! use std::process::{Command, Stdio};
+//! use tracel_experiment::integration::output::OutputCapture;
+//!
+//! let mut child = Command::new("my-trainer")
+//!     .stdout(Stdio::piped())
+//!     .stderr(Stdio::piped())
+//!     .spawn()?;
+//! let capture = OutputCapture::new(&experiment).attach(&mut child);
+//! let status = child.wait()?;
+//! capture.join();
+//! ```
+//!
+//! The output of the current process is forwarded with [`OutputCapture::capture_process`], which
+//! [`crate::ExperimentJob::capture_output`] enables for the duration of a job.
+
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{ExperimentRunHandle, LogRecord};
+
+const DEFAULT_MAX_CHUNK_BYTES: usize = 16 * 1024;
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+/// Read errors in a row after which a stream is considered broken and no longer read.
+const MAX_CONSECUTIVE_READ_ERRORS: usize = 16;
+
+/// Whether a [`ProcessOutputCapture`] currently redirects this process's standard streams.
+static PROCESS_CAPTURE_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The standard stream a captured line was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    /// The lowercase name of the stream, used as the `stream` log attribute.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Forwards text streams into an experiment's logs as chunked [`LogRecord`]s.
+///
+/// Every record carries a `stream` attribute naming the source stream. Output is forwarded at
+/// `info` level regardless of the stream, since most loggers write ordinary progress to stderr.
+#[derive(Clone)]
+pub struct OutputCapture {
+    experiment: ExperimentRunHandle,
+    max_chunk_bytes: usize,
+    flush_interval: Duration,
+}
+
+impl OutputCapture {
+    /// Create a capture that forwards into `experiment`.
+    pub fn new(experiment: impl Into<ExperimentRunHandle>) -> Self {
+        Self {
+            experiment: experiment.into(),
+            max_chunk_bytes: DEFAULT_MAX_CHUNK_BYTES,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+        }
+    }
+
+    /// Maximum size of a single forwarded chunk, in bytes. Defaults to 16 KiB.
+    ///
+    /// Lines longer than this, such as progress bars redrawn with `\r`, are split into pieces of
+    /// at most this size, so a stream without newlines is never buffered without bound.
+    #[must_use]
+    pub fn max_chunk_bytes(mut self, max_chunk_bytes: usize) -> Self {
+        self.max_chunk_bytes = max_chunk_bytes.max(1);
+        self
+    }
+
+    /// Maximum time a line stays buffered before it is forwarded. Defaults to 500 ms.
+    ///
+    /// Lines read while a chunk is buffered are sent with it, so a busy stream produces at most
+    /// about one log event per interval, and a quiet one still forwards its last lines on time.
+    #[must_use]
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Forward `reader` line by line on a background thread until it reaches end of file.
+    ///
+    /// Any buffered output is flushed when the stream ends. Invalid UTF-8 is replaced and read
+    /// errors are skipped, so the stream keeps being drained and its writer never sees a closed
+    /// pipe; only a stream that keeps failing is abandoned.
+    pub fn forward<R>(&self, stream: OutputStream, reader: R) -> JoinHandle<()>
+    where
+        R: Read + Send + 'static,
+    {
+        self.spawn(stream, reader, None)
+    }
+
+    /// Take the piped stdout and stderr of `child` and forward both into the experiment.
+    ///
+    /// Streams that were not configured with [`std::process::Stdio::piped`] are left untouched.
+    pub fn attach(&self, child: &mut Child) -> OutputCaptureGuard {
+        let mut workers = Vec::with_capacity(2);
+        if let Some(stdout) = child.stdout.take() {
+            workers.push(self.forward(OutputStream::Stdout, stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            workers.push(self.forward(OutputStream::Stderr, stderr));
+        }
+        OutputCaptureGuard { workers }
+    }
+
+    /// Forward the stdout and stderr of the current process until the returned guard is dropped.
+    ///
+    /// The output still reaches the original streams. Returns `None`, after logging a warning,
+    /// when another capture is already active, when the streams cannot be redirected, or on
+    /// platforms other than Unix.
+    pub fn capture_process(&self) -> Option<ProcessOutputCapture> {
+        if PROCESS_CAPTURE_ACTIVE.swap(true, Ordering::SeqCst) {
+            tracing::warn!("Process output is already being captured");
+            return None;
+        }
+
+        let mut capture = ProcessOutputCapture {
+            #[cfg(unix)]
+            redirects: Vec::with_capacity(2),
+            workers: Vec::with_capacity(2),
+        };
+        match self.redirect_standard_streams(&mut capture) {
+            Ok(()) => Some(capture),
+            Err(err) => {
+                tracing::warn!("Failed to capture process output: {err}");
+                None
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn redirect_standard_streams(&self, capture: &mut ProcessOutputCapture) -> std::io::Result<()> {
+        // Output written before the capture starts still goes to the original streams.
+        let _ = std::io::stdout().flush();
+        for (stream, fd) in [(OutputStream::Stdout, 1), (OutputStream::Stderr, 2)] {
+            let (redirect, reader, original) = redirect::Redirect::new(fd)?;
+            capture.redirects.push(redirect);
+            capture
+                .workers
+                .push(self.spawn(stream, reader, Some(Box::new(original))));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn redirect_standard_streams(
+        &self,
+        _capture: &mut ProcessOutputCapture,
+    ) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "only supported on Unix",
+        ))
+    }
+
+    fn spawn<R>(
+        &self,
+        stream: OutputStream,
+        reader: R,
+        tee: Option<Box<dyn Write + Send>>,
+    ) -> JoinHandle<()>
+    where
+        R: Read + Send + 'static,
+    {
+        let capture = self.clone();
+        thread::spawn(move || capture.run(stream, reader, tee))
+    }
+
+    fn run<R: Read + Send + 'static>(
+        &self,
+        stream: OutputStream,
+        reader: R,
+        tee: Option<Box<dyn Write + Send>>,
+    ) {
+        // Lines are read on their own thread so a blocked read does not hold back the flush.
+        let (lines, received) = mpsc::channel();
+        let max_line_bytes = self.max_chunk_bytes;
+        thread::spawn(move || read_lines(reader, max_line_bytes, tee, lines));
+
+        let mut chunk = String::new();
+        // When the first line of the current chunk was read.
+        let mut buffered_since = Instant::now();
+        loop {
+            let line = if chunk.is_empty() {
+                match received.recv() {
+                    Ok(line) => line,
+                    Err(_) => break,
+                }
+            } else {
+                let deadline = buffered_since + self.flush_interval;
+                match received.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line) => line,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.flush(stream, &mut chunk);
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            };
+
+            if !chunk.is_empty() && chunk.len() + line.len() > self.max_chunk_bytes {
+                self.flush(stream, &mut chunk);
+            }
+            if chunk.is_empty() {
+                buffered_since = Instant::now();
+            }
+            chunk.push_str(&line);
+
+            if chunk.len() >= self.max_chunk_bytes
+                || buffered_since.elapsed() >= self.flush_interval
+            {
+                self.flush(stream, &mut chunk);
+            }
+        }
+
+        self.flush(stream, &mut chunk);
+    }
+
+    fn flush(&self, stream: OutputStream, chunk: &mut String) {
+        let message = chunk.trim_end_matches(['\n', '\r']);
+        if !message.is_empty() {
+            let record = LogRecord::info(message).with("stream", stream.as_str());
+            let _ = self.experiment.log(record);
+        }
+        chunk.clear();
+    }
+}
+
+/// Read `reader` until end of file, sending lines of at most `max_line_bytes` to `lines`.
+///
+/// Bytes are copied to `tee` as soon as they are read, newline or not.
+fn read_lines<R: Read>(
+    reader: R,
+    max_line_bytes: usize,
+    mut tee: Option<Box<dyn Write + Send>>,
+    lines: mpsc::Sender<String>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    let mut errors = 0;
+    loop {
+        let available = match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(available) => available,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => {
+                errors += 1;
+                if errors >= MAX_CONSECUTIVE_READ_ERRORS {
+                    tracing::warn!("Stopped capturing output after repeated read errors: {err}");
+                    break;
+                }
+                continue;
+            }
+        };
+        errors = 0;
+
+        let end = available
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(available.len(), |newline| newline + 1)
+            .min(max_line_bytes - line.len());
+        let bytes = &available[..end];
+        if tee
+            .as_mut()
+            .is_some_and(|tee| tee.write_all(bytes).is_err())
+        {
+            tee = None;
+        }
+        line.extend_from_slice(bytes);
+        reader.consume(end);
+
+        if line.ends_with(b"\n") || line.len() >= max_line_bytes {
+            // A closed receiver is ignored so the stream keeps being drained.
+            let _ = lines.send(String::from_utf8_lossy(&line).into_owned());
+            line.clear();
+        }
+    }
+
+    if !line.is_empty() {
+        let _ = lines.send(String::from_utf8_lossy(&line).into_owned());
+    }
+}
+
+/// Background forwarders started by [`OutputCapture::attach`].
+///
+/// Call [`Self::join`] after the child exits to make sure its remaining output was forwarded.
+#[must_use = "join the capture to wait for the remaining output to be forwarded"]
+pub struct OutputCaptureGuard {
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl OutputCaptureGuard {
+    /// Wait until every captured stream has reached end of file and been flushed.
+    pub fn join(self) {
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Redirection of the current process's stdout and stderr, see [`OutputCapture::capture_process`].
+///
+/// Dropping it restores the original streams and waits until the captured output has been
+/// forwarded. Child processes spawned meanwhile inherit the redirected streams, so the drop also
+/// waits for those that are still running to exit or close them.
+#[must_use = "the process output is only captured while the guard is alive"]
+pub struct ProcessOutputCapture {
+    #[cfg(unix)]
+    redirects: Vec<redirect::Redirect>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Drop for ProcessOutputCapture {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        // Restoring the streams closes the pipes, which ends the forwarders.
+        #[cfg(unix)]
+        self.redirects.clear();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+        PROCESS_CAPTURE_ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(unix)]
+mod redirect {
+    use std::fs::File;
+    use std::io::{self, PipeReader};
+    use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd, RawFd};
+
+    /// A standard stream pointed at a pipe, restored to its original target on drop.
+    pub(super) struct Redirect {
+        fd: RawFd,
+        original: OwnedFd,
+    }
+
+    impl Redirect {
+        /// Point `fd` at a new pipe, returning the pipe's read end and a handle to the original
+        /// target of `fd`.
+        pub(super) fn new(fd: RawFd) -> io::Result<(Self, PipeReader, File)> {
+            // SAFETY: the standard streams stay open for the whole life of the process.
+            let original = unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?;
+            let tee = File::from(original.try_clone()?);
+            let (reader, writer) = io::pipe()?;
+            // SAFETY: both descriptors are open, and dup2 replaces `fd` atomically.
+            if unsafe { libc::dup2(writer.as_raw_fd(), fd) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok((Self { fd, original }, reader, tee))
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            // SAFETY: both descriptors are open; this closes the pipe's last write end.
+            unsafe { libc::dup2(self.original.as_raw_fd(), self.fd) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use crate::error::ExperimentError;
+    use crate::reader::{ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact};
+    use crate::session::{BundleFn, Event, ExperimentCompletion, ExperimentSession};
+    use crate::{ArtifactKind, CancelToken, ExperimentId, ExperimentRun};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockSession {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl ExperimentSession for MockSession {
+        fn record_event(&self, event: Event) -> Result<(), ExperimentError> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn save_artifact(
+            &self,
+            _name: &str,
+            _kind: ArtifactKind,
+            _artifact: Box<BundleFn>,
+        ) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn finish(&self, _completion: ExperimentCompletion) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+    }
+
+    struct NoopExperimentDataReader;
+
+    impl ExperimentArtifactReader for NoopExperimentDataReader {
+        fn load_artifact_raw(
+            &self,
+            _experiment_id: ExperimentId,
+            _name: &str,
+        ) -> Result<LoadedArtifact, ExperimentReaderError> {
+            Err(ExperimentReaderError::new("Artifact not found"))
+        }
+    }
+
+    fn create_run(session: Arc<MockSession>) -> ExperimentRun {
+        ExperimentRun::new(
+            "output-capture",
+            session,
+            NoopExperimentDataReader,
+            CancelToken::default(),
+        )
+    }
+
+    fn logged_messages(session: &MockSession) -> Vec<(String, String)> {
+        session
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Log(record) => Some((
+                    record.message.clone(),
+                    record.attributes["stream"].as_str().unwrap().to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn forward_groups_lines_into_a_single_chunk_within_the_interval() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        OutputCapture::new(&run)
+            .flush_interval(Duration::from_secs(60))
+            .forward(OutputStream::Stdout, Cursor::new("epoch 1\nepoch 2\n"))
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            logged_messages(&session),
+            vec![("epoch 1\nepoch 2".to_string(), "stdout".to_string())]
+        );
+    }
+
+    #[test]
+    fn forward_splits_chunks_at_the_size_limit() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        OutputCapture::new(&run)
+            .max_chunk_bytes(8)
+            .flush_interval(Duration::from_secs(60))
+            .forward(OutputStream::Stderr, Cursor::new("aaaa\nbbbb\ncccc\n"))
+            .join()
+            .unwrap();
+
+        let messages: Vec<String> = logged_messages(&session)
+            .into_iter()
+            .map(|(message, stream)| {
+                assert_eq!(stream, "stderr");
+                message
+            })
+            .collect();
+        assert_eq!(messages, vec!["aaaa", "bbbb", "cccc"]);
+    }
+
+    /// A stream fed from a channel, blocking until the next chunk or until the sender is dropped.
+    struct ChannelReader {
+        chunks: mpsc::Receiver<Vec<u8>>,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Ok(chunk) = self.chunks.recv() else {
+                return Ok(0);
+            };
+            buf[..chunk.len()].copy_from_slice(&chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn forward_flushes_buffered_lines_while_the_stream_is_quiet() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+        let (sender, chunks) = mpsc::channel();
+
+        let worker = OutputCapture::new(&run)
+            .flush_interval(Duration::from_millis(10))
+            .forward(OutputStream::Stdout, ChannelReader { chunks });
+        sender.send(b"epoch 1\n".to_vec()).unwrap();
+
+        let started = Instant::now();
+        while logged_messages(&session).is_empty() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "line was not flushed"
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            logged_messages(&session),
+            vec![("epoch 1".to_string(), "stdout".to_string())]
+        );
+
+        drop(sender);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn forward_keeps_reading_past_invalid_utf8() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        OutputCapture::new(&run)
+            .flush_interval(Duration::from_secs(60))
+            .forward(
+                OutputStream::Stdout,
+                Cursor::new(b"loss \xff\xfe 0.5\nepoch 2\n".to_vec()),
+            )
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            logged_messages(&session),
+            vec![(
+                "loss \u{fffd}\u{fffd} 0.5\nepoch 2".to_string(),
+                "stdout".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn forward_splits_lines_without_a_newline() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        OutputCapture::new(&run)
+            .max_chunk_bytes(4)
+            .flush_interval(Duration::from_secs(60))
+            .forward(OutputStream::Stderr, Cursor::new("10%\r20%\r30%\r"))
+            .join()
+            .unwrap();
+
+        let messages: Vec<String> = logged_messages(&session)
+            .into_iter()
+            .map(|(message, _)| message)
+            .collect();
+        assert_eq!(messages, vec!["10%", "20%", "30%"]);
+    }
+
+    /// A writer appending to a shared buffer.
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tee_receives_the_raw_output() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+        let tee = SharedWriter::default();
+
+        OutputCapture::new(&run)
+            .spawn(
+                OutputStream::Stdout,
+                Cursor::new(b"step\r\xff\n".to_vec()),
+                Some(Box::new(tee.clone())),
+            )
+            .join()
+            .unwrap();
+
+        assert_eq!(*tee.0.lock().unwrap(), b"step\r\xff\n");
+        assert_eq!(logged_messages(&session).len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn capture_process_forwards_the_standard_streams() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        let capture = OutputCapture::new(&run).capture_process().unwrap();
+        assert!(OutputCapture::new(&run).capture_process().is_none());
+        // Written to the descriptor directly, bypassing the test harness's output capture.
+        std::io::stderr()
+            .write_all(b"captured from the process\n")
+            .unwrap();
+        drop(capture);
+
+        assert!(logged_messages(&session).iter().any(|(message, stream)| {
+            message.contains("captured from the process") && stream == "stderr"
+        }));
+    }
+
+    #[test]
+    fn forward_skips_empty_output() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        OutputCapture::new(&run)
+            .forward(OutputStream::Stdout, Cursor::new(""))
+            .join()
+            .unwrap();
+
+        assert!(logged_messages(&session).is_empty());
+    }
+}
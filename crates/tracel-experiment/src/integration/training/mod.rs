@@ -5,7 +5,7 @@
 //! backend.
 //!
 //! Import [`ExperimentTrainingExt`] for the ergonomic constructors, or use the concrete adapter
-//! types directly.
+//! types directly. [`ExperimentTrainingExt::training_adapters`] creates all of them at once.
 //!
 //! # Example
 //!
@@ -18,6 +18,9 @@
 //! let _metrics = experiment.metric_logger();
 //! let _checkpoints = experiment.checkpointers();
 //! let _interrupter = experiment.interrupter();
+//!
+//! // Or everything a learner needs, resuming checkpoints from a previous run.
+//! let _adapters = experiment.training_adapters().restore_from("previous-run");
 //! ```
 
 mod checkpoint;
@@ -67,6 +70,49 @@ pub trait ExperimentTrainingExt {
 
     /// Create a new [`ExperimentEvaluationProgressLogger`] for this run.
     fn evaluation_progress_logger(&self) -> ExperimentEvaluationProgressLogger;
+
+    /// Create every adapter of a supervised learner at once.
+    fn training_adapters(&self) -> TrainingAdapters {
+        let (model_checkpointer, optim_checkpointer, scheduler_checkpointer) = self.checkpointers();
+        TrainingAdapters {
+            metric_logger: self.metric_logger(),
+            model_checkpointer,
+            optim_checkpointer,
+            scheduler_checkpointer,
+            interrupter: self.interrupter(),
+            training_progress_logger: self.training_progress_logger(),
+            evaluation_progress_logger: self.evaluation_progress_logger(),
+        }
+    }
+}
+
+/// The adapters a supervised learner needs, created by
+/// [`ExperimentTrainingExt::training_adapters`].
+///
+/// Each field is handed to the matching option of the learner, so metrics, checkpoints and
+/// cancellation go through the run without any manual experiment call in the training function.
+pub struct TrainingAdapters {
+    pub metric_logger: ExperimentMetricLogger,
+    pub model_checkpointer: ExperimentCheckpointer,
+    pub optim_checkpointer: ExperimentCheckpointer,
+    pub scheduler_checkpointer: ExperimentCheckpointer,
+    pub interrupter: burn::train::Interrupter,
+    pub training_progress_logger: ExperimentTrainingProgressLogger,
+    pub evaluation_progress_logger: ExperimentEvaluationProgressLogger,
+}
+
+impl TrainingAdapters {
+    /// Restore checkpoints from `source_id` when resuming, while still saving to the current run.
+    ///
+    /// See [`ExperimentTrainingExt::checkpointers_from`].
+    #[must_use]
+    pub fn restore_from(mut self, source_id: impl Into<ExperimentId>) -> Self {
+        let id = source_id.into();
+        self.model_checkpointer = self.model_checkpointer.with_restore_from(id.clone());
+        self.optim_checkpointer = self.optim_checkpointer.with_restore_from(id.clone());
+        self.scheduler_checkpointer = self.scheduler_checkpointer.with_restore_from(id);
+        self
+    }
 }
 
 impl ExperimentTrainingExt for ExperimentRun {
@@ -0,0 +1,104 @@
+//! Ctrl-C handling for running experiment jobs.
+//!
+//! Without a handler, SIGINT kills the process and leaves the experiment in the running state.
+//! Jobs opted in with [`crate::ExperimentJob::cancel_on_interrupt`] register their run's cancel
+//! token here. The first Ctrl-C cancels every registered run, so jobs can stop at the next
+//! cancellation check, save what they have, and be finalized as cancelled. A second Ctrl-C exits
+//! immediately, and so does a Ctrl-C while no run is registered, as if no handler was installed.
+
+use std::sync::Mutex;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::CancelToken;
+
+/// Exit code of a process terminated by SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static INSTALL_HANDLER: Once = Once::new();
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static ACTIVE: Mutex<Vec<(u64, CancelToken)>> = Mutex::new(Vec::new());
+
+/// Keeps a run registered for interrupt cancellation until dropped.
+pub(crate) struct InterruptGuard {
+    id: u64,
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        let mut active = ACTIVE.lock().unwrap();
+        active.retain(|(id, _)| *id != self.id);
+        // Once every interrupted run is gone, the next job starts with a fresh first Ctrl-C.
+        if active.is_empty() {
+            INTERRUPTED.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Cancel `token` on Ctrl-C for as long as the returned guard is alive.
+pub(crate) fn register(token: CancelToken) -> InterruptGuard {
+    install_handler();
+    track(token)
+}
+
+fn track(token: CancelToken) -> InterruptGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    ACTIVE.lock().unwrap().push((id, token));
+    InterruptGuard { id }
+}
+
+fn install_handler() {
+    INSTALL_HANDLER.call_once(|| {
+        if let Err(err) = ctrlc::set_handler(on_interrupt) {
+            tracing::warn!(
+                "Failed to install Ctrl-C handler, interrupts will not be handled: {err}"
+            );
+        }
+    });
+}
+
+fn on_interrupt() {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+
+    if !cancel_active() {
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+    tracing::warn!(
+        "Interrupted, finalizing running experiments as cancelled. \
+         Press Ctrl-C again to exit immediately."
+    );
+}
+
+/// Cancel every registered run. Returns `false` when there was none.
+fn cancel_active() -> bool {
+    let active = ACTIVE.lock().unwrap().clone();
+    for (_, token) in &active {
+        token.cancel();
+    }
+    !active.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_active_cancels_registered_tokens_only() {
+        let registered = CancelToken::new();
+        let released = CancelToken::new();
+        let guard = track(registered.clone());
+        drop(track(released.clone()));
+
+        assert!(cancel_active());
+
+        assert!(registered.is_cancelled());
+        assert!(!released.is_cancelled());
+
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        drop(guard);
+        assert!(!INTERRUPTED.load(Ordering::SeqCst));
+    }
+}
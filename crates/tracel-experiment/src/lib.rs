@@ -15,21 +15,29 @@
 //! [`ExperimentJob`] are the user-facing entry points for running a job and logging its result.
 
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use tracel_artifact::bundle::{BundleDecode, BundleEncode, FsBundle};
 
 use serde::Serialize;
 
 mod activity;
+mod anomaly;
 mod cancellation;
 mod context;
 mod control;
+mod hook;
+mod interrupt;
 mod log;
+mod memory;
+mod panic;
 mod provider;
 pub mod reader;
+mod retry;
 pub mod session;
+mod workdir;
 
 pub mod error;
 pub mod integration;
@@ -38,19 +46,28 @@ pub use activity::{
     Activity, ActivityBuilder, ActivityEvent, ActivityGuard, ActivityId, ActivityMeter,
     ActivityStatus, Metered, Unmetered,
 };
+pub use anomaly::{AnomalyDetector, AnomalyKind, MetricAnomaly};
 pub use cancellation::{CancelToken, Cancellable};
 pub use context::{
     CurrentExperimentGuard, ExperimentGlobalExt, ExperimentInstrument, WithCurrentExperiment,
 };
 pub use control::ExperimentRunControl;
+pub use hook::ExperimentHook;
 pub use log::{LogLevel, LogRecord};
-pub use provider::{ExperimentFn, ExperimentJob, ExperimentModule, ExperimentProvider};
+pub use memory::{MEMORY_SPLIT, MemoryTracker};
+pub use provider::{
+    ExperimentFn, ExperimentJob, ExperimentJobHandle, ExperimentModule, ExperimentProvider,
+};
+pub use retry::OomBackoff;
 
 use crate::activity::{ActivityEventReporter, AtomicActivityIdAllocator};
 use crate::error::{ExperimentError, ExperimentErrorKind};
 use crate::integration::tracing::registry::{TracingRegistration, TracingRegistry};
 use crate::reader::ExperimentArtifactReader;
-use crate::session::{Event, ExperimentCompletion, ExperimentSession};
+use crate::session::{
+    Event, ExperimentCompletion, ExperimentFailure, ExperimentSession, FailureClass,
+};
+use crate::workdir::RunDirs;
 
 /// Opaque identifier for an experiment run.
 ///
@@ -149,8 +166,12 @@ pub struct MetricValue {
 #[derive(Debug, Clone)]
 struct ExperimentMetadata {
     pub id: ExperimentId,
+    pub name: Option<String>,
 }
 
+/// Identifies a run across experiments, since backends only guarantee unique ids per experiment.
+pub(crate) type RunKey = (Option<String>, ExperimentId);
+
 /// An active experiment run.
 ///
 /// `ExperimentRun` owns finalization. As long as the run remains active, it can log structured
@@ -192,6 +213,8 @@ struct RunInner {
     session: Box<dyn ExperimentSession>,
     reader: Box<dyn ExperimentArtifactReader>,
     activity_id_allocator: Arc<AtomicActivityIdAllocator>,
+    hooks: RwLock<Vec<Arc<dyn ExperimentHook>>>,
+    dirs: RunDirs,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -232,13 +255,18 @@ impl ExperimentRun {
         S: ExperimentSession + 'static,
         R: ExperimentArtifactReader + 'static,
     {
-        let metadata = ExperimentMetadata { id: id.into() };
+        let metadata = ExperimentMetadata {
+            id: id.into(),
+            name: None,
+        };
         let inner = Arc::new(RunInner {
             control: control.clone(),
             state: Mutex::new(RunState::Active),
             session: Box::new(session),
             reader: Box::new(reader),
             activity_id_allocator: Arc::new(AtomicActivityIdAllocator::new()),
+            hooks: RwLock::new(Vec::new()),
+            dirs: RunDirs::new(),
         });
 
         let handle = ExperimentRunHandle {
@@ -284,10 +312,32 @@ impl ExperimentRun {
 
     /// Mark the run as failed and finalize the backend session.
     ///
-    /// Any cloned [`ExperimentRunHandle`] becomes inactive afterwards.
+    /// The failure is classified as [`FailureClass::User`]; use [`Self::fail_with`] to record a
+    /// different class. Any cloned [`ExperimentRunHandle`] becomes inactive afterwards.
     pub fn fail(self, reason: impl Into<String>) -> Result<(), ExperimentError> {
+        self.fail_with(ExperimentFailure::new(FailureClass::User, reason))
+    }
+
+    /// Mark the run as failed with a classified failure and finalize the backend session.
+    ///
+    /// Any cloned [`ExperimentRunHandle`] becomes inactive afterwards.
+    pub fn fail_with(self, failure: ExperimentFailure) -> Result<(), ExperimentError> {
+        self.inner
+            .finish_once(ExperimentCompletion::Failed(failure))
+    }
+
+    /// Mark the run as cancelled and finalize the backend session.
+    pub(crate) fn finish_cancelled(self) -> Result<(), ExperimentError> {
+        self.inner.finish_once(ExperimentCompletion::Cancelled)
+    }
+
+    /// Register lifecycle hooks that observe events recorded by this run.
+    pub(crate) fn add_hooks(&self, hooks: &[Arc<dyn ExperimentHook>]) {
         self.inner
-            .finish_once(ExperimentCompletion::Failed(reason.into()))
+            .hooks
+            .write()
+            .unwrap()
+            .extend(hooks.iter().cloned());
     }
 }
 
@@ -303,6 +353,15 @@ impl ExperimentRun {
         self.handle.id()
     }
 
+    /// Record the name of the experiment this run belongs to.
+    ///
+    /// Handles cloned after this call report it through [`ExperimentRunHandle::experiment_name`].
+    #[must_use]
+    pub fn with_experiment_name(mut self, name: impl Into<String>) -> Self {
+        self.handle.metadata.name = Some(name.into());
+        self
+    }
+
     /// Log the serialized input arguments for the run.
     pub fn log_args<A: Serialize>(&self, args: &A) -> Result<(), ExperimentError> {
         self.handle.log_args(args)
@@ -411,6 +470,9 @@ impl ExperimentRun {
     }
 
     /// Load and decode an artifact from a compatible experiment identifier.
+    ///
+    /// The artifact is recorded as an input of this run, so the backend can track the lineage
+    /// from the source experiment to this one.
     pub fn use_artifact<D: BundleDecode>(
         &self,
         experiment_id: impl Into<ExperimentId>,
@@ -420,6 +482,27 @@ impl ExperimentRun {
         self.handle.use_artifact(experiment_id, name, settings)
     }
 
+    /// Return a directory for temporary files, removed when the run is finalized.
+    ///
+    /// The directory is private to this run and created on first use.
+    pub fn scratch_dir(&self) -> Result<PathBuf, ExperimentError> {
+        self.handle.scratch_dir()
+    }
+
+    /// Return a directory whose contents are saved as artifact `name` when the run is finalized.
+    ///
+    /// This suits outputs written by external tools or libraries that expect a path. The
+    /// directory is saved whatever the run's outcome, so partial outputs of a failed or cancelled
+    /// run are kept; it is skipped if left empty. Asking again for the same name returns the same
+    /// directory.
+    pub fn artifact_dir(
+        &self,
+        name: impl AsRef<str>,
+        kind: ArtifactKind,
+    ) -> Result<PathBuf, ExperimentError> {
+        self.handle.artifact_dir(name, kind)
+    }
+
     /// Create an activity builder for the run with the provided name.
     ///
     /// Call [`ActivityBuilder::progress`] before starting when the activity should have a numeric
@@ -463,6 +546,15 @@ impl ExperimentRunHandle {
         &self.metadata.id
     }
 
+    /// Borrow the name of the experiment this run belongs to, when it is known.
+    pub fn experiment_name(&self) -> Option<&str> {
+        self.metadata.name.as_deref()
+    }
+
+    pub(crate) fn run_key(&self) -> RunKey {
+        (self.metadata.name.clone(), self.metadata.id.clone())
+    }
+
     /// Return a cancellation token that can be linked to child work.
     ///
     /// Cancelling the token does not finish the run; it only broadcasts cancellation to linked
@@ -636,7 +728,7 @@ impl ExperimentRunHandle {
         let experiment_id = experiment_id.into();
         let artifact = inner
             .reader
-            .load_artifact_raw(experiment_id, name_str)
+            .load_artifact_raw(experiment_id.clone(), name_str)
             .map_err(|e| {
                 ExperimentError::with_source(
                     ExperimentErrorKind::Artifact,
@@ -645,13 +737,39 @@ impl ExperimentRunHandle {
                 )
             })?;
 
-        D::decode(&artifact.bundle, settings).map_err(|e| {
+        let decoded = D::decode(&artifact.bundle, settings).map_err(|e| {
             ExperimentError::with_source(
                 ExperimentErrorKind::Artifact,
                 format!("Failed to decode artifact: {name_str}"),
                 e,
             )
-        })
+        })?;
+
+        // Record the lineage edge from the source experiment's artifact to this run.
+        self.record_event(Event::ArtifactUsed {
+            experiment_id,
+            reference: artifact.reference,
+        })?;
+
+        Ok(decoded)
+    }
+
+    /// See [`ExperimentRun::scratch_dir`].
+    pub fn scratch_dir(&self) -> Result<PathBuf, ExperimentError> {
+        let inner = self.upgrade()?;
+        inner.ensure_active()?;
+        inner.dirs.scratch()
+    }
+
+    /// See [`ExperimentRun::artifact_dir`].
+    pub fn artifact_dir(
+        &self,
+        name: impl AsRef<str>,
+        kind: ArtifactKind,
+    ) -> Result<PathBuf, ExperimentError> {
+        let inner = self.upgrade()?;
+        inner.ensure_active()?;
+        inner.dirs.artifact(name.as_ref(), kind)
     }
 
     /// See [`ExperimentRun::activity`].
@@ -694,9 +812,33 @@ impl ExperimentRunHandle {
     fn record_event(&self, event: Event) -> Result<(), ExperimentError> {
         let inner = self.upgrade()?;
         inner.ensure_active()?;
+        if let Event::Metrics {
+            epoch,
+            split,
+            iteration,
+            items,
+        } = &event
+        {
+            // Hooks may log through the run themselves, so the lock is not held while they run.
+            let hooks = inner.hooks.read().unwrap().clone();
+            for hook in &hooks {
+                hook.on_metric(self, *epoch, split, *iteration, items);
+            }
+        }
         inner.session.record_event(event)
     }
 
+    /// Notify every hook of the run that `anomaly` was detected.
+    pub(crate) fn notify_anomaly(&self, anomaly: &MetricAnomaly) {
+        let Ok(inner) = self.upgrade() else {
+            return;
+        };
+        let hooks = inner.hooks.read().unwrap().clone();
+        for hook in &hooks {
+            hook.on_anomaly(self, anomaly);
+        }
+    }
+
     fn upgrade(&self) -> Result<Arc<RunInner>, ExperimentError> {
         self.inner.upgrade().ok_or(ExperimentError::new(
             ExperimentErrorKind::InactiveRun,
@@ -727,6 +869,7 @@ impl RunInner {
             RunState::Active => {
                 *state = RunState::Finished;
                 drop(state);
+                self.dirs.finalize(&*self.session);
                 self.session.finish(completion)
             }
         }
@@ -750,8 +893,10 @@ impl Drop for ExperimentRun {
 mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    use tracel_artifact::bundle::BundleSource;
+
     use crate::activity::ActivityEvent;
-    use crate::reader::{ExperimentReaderError, LoadedArtifact};
+    use crate::reader::{ArtifactRef, ExperimentReaderError, LoadedArtifact};
     use crate::session::BundleFn;
 
     use super::*;
@@ -798,6 +943,35 @@ mod tests {
         }
     }
 
+    struct SingleArtifactReader;
+
+    impl ExperimentArtifactReader for SingleArtifactReader {
+        fn load_artifact_raw(
+            &self,
+            _experiment_id: ExperimentId,
+            name: &str,
+        ) -> Result<LoadedArtifact, ExperimentReaderError> {
+            let bundle = FsBundle::temp()
+                .map_err(|e| ExperimentReaderError::with_source("Failed to create bundle", e))?;
+            let reference = ArtifactRef {
+                id: "artifact-1".to_string(),
+                name: name.to_string(),
+            };
+            Ok(LoadedArtifact::new(reference, bundle))
+        }
+    }
+
+    struct Checkpoint;
+
+    impl BundleDecode for Checkpoint {
+        type Settings = ();
+        type Error = String;
+
+        fn decode<I: BundleSource>(_source: &I, _settings: &()) -> Result<Self, String> {
+            Ok(Checkpoint)
+        }
+    }
+
     fn create_run(session: Arc<MockSession>) -> ExperimentRun {
         ExperimentRun::new(
             "test/experiment/1",
@@ -892,6 +1066,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn use_artifact_records_the_source_experiment() {
+        let session = Arc::new(MockSession::default());
+        let run = ExperimentRun::new(
+            "fine-tune",
+            session.clone(),
+            SingleArtifactReader,
+            CancelToken::default(),
+        );
+
+        run.use_artifact::<Checkpoint>("pretrain", "model", &())
+            .unwrap();
+
+        let events = session.events.lock().unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [Event::ArtifactUsed { experiment_id, reference }]
+                if experiment_id.as_str() == "pretrain" && reference.id == "artifact-1"
+        ));
+    }
+
+    #[test]
+    fn artifact_dirs_are_saved_and_removed_on_finish() {
+        let session = Arc::new(MockSession::default());
+        let run = create_run(session.clone());
+
+        let output = run.artifact_dir("plots", ArtifactKind::Other).unwrap();
+        std::fs::write(output.join("loss.svg"), "<svg/>").unwrap();
+        run.artifact_dir("empty", ArtifactKind::Other).unwrap();
+        let scratch = run.scratch_dir().unwrap();
+        assert!(run.artifact_dir("../escape", ArtifactKind::Other).is_err());
+
+        run.finish().unwrap();
+
+        assert_eq!(session.artifacts_saved.load(Ordering::Acquire), 1);
+        assert!(!output.exists());
+        assert!(!scratch.exists());
+    }
+
     #[test]
     fn finish_marks_handle_inactive() {
         let session = Arc::new(MockSession::default());
@@ -0,0 +1,251 @@
+//! Per-epoch memory tracking for training runs.
+//!
+//! [`MemoryTracker`] is an opt-in [`ExperimentHook`] that samples host and backend memory each
+//! time a run moves to a new epoch, and once more when the job returns. Samples are logged as
+//! metrics of the `memory` split, so out-of-memory-prone configurations stand out on the
+//! dashboard:
+//!
+//! ```ignore
+//! let job = module
+//!     .create("train", train)
+//!     .hook(MemoryTracker::new().backend_memory(|| Some(device_memory_in_use())));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::session::{ExperimentCompletion, ExperimentFailure};
+use crate::{ExperimentHook, ExperimentRunHandle, MetricSpec, MetricValue, RunKey};
+
+/// Split the memory samples are logged under.
+pub const MEMORY_SPLIT: &str = "memory";
+
+const HOST_RSS: &str = "host_rss_mb";
+const HOST_PEAK_RSS: &str = "host_peak_rss_mb";
+const BACKEND_MEMORY: &str = "backend_memory_mb";
+
+type BackendMemoryFn = dyn Fn() -> Option<u64> + Send + Sync;
+
+/// Experiment hook logging host and backend memory usage once per epoch.
+///
+/// Host memory is read from `/proc/self/status` and is only available on Linux. Backend memory is
+/// whatever the function given to [`Self::backend_memory`] reports.
+#[derive(Clone, Default)]
+pub struct MemoryTracker {
+    backend_memory: Option<Arc<BackendMemoryFn>>,
+    /// Last epoch seen per run, keyed by experiment name and id since one hook can observe several
+    /// jobs.
+    epochs: Arc<Mutex<HashMap<RunKey, usize>>>,
+}
+
+impl MemoryTracker {
+    /// Create a tracker that samples host memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also sample backend memory, in bytes, with `memory_in_use`.
+    ///
+    /// Returning `None` skips the backend sample for that epoch.
+    #[must_use]
+    pub fn backend_memory(
+        mut self,
+        memory_in_use: impl Fn() -> Option<u64> + Send + Sync + 'static,
+    ) -> Self {
+        self.backend_memory = Some(Arc::new(memory_in_use));
+        self
+    }
+
+    fn sample(&self, run: &ExperimentRunHandle, epoch: usize) {
+        let mut items = Vec::new();
+        if let Some(host) = host_memory() {
+            items.push(mb_value(HOST_RSS, host.rss_kb * 1024));
+            items.push(mb_value(HOST_PEAK_RSS, host.peak_rss_kb * 1024));
+        }
+        if let Some(bytes) = self.backend_memory.as_ref().and_then(|memory| memory()) {
+            items.push(mb_value(BACKEND_MEMORY, bytes));
+        }
+        if items.is_empty() {
+            return;
+        }
+
+        if let Err(err) = run.log_metric(epoch, MEMORY_SPLIT, epoch, items) {
+            tracing::warn!(experiment_id = %run.id(), "Failed to log memory usage: {err}");
+        }
+    }
+
+    fn sample_last_epoch(&self, run: &ExperimentRunHandle) {
+        let epoch = self.epochs.lock().unwrap().get(&run.run_key()).copied();
+        self.sample(run, epoch.unwrap_or_default());
+    }
+}
+
+impl ExperimentHook for MemoryTracker {
+    fn before_run(&self, run: &ExperimentRunHandle) {
+        for name in [HOST_RSS, HOST_PEAK_RSS, BACKEND_MEMORY] {
+            let _ = run.log_metric_definition(MetricSpec {
+                name: name.to_string(),
+                description: None,
+                unit: Some("MB".to_string()),
+                higher_is_better: false,
+            });
+        }
+    }
+
+    fn after_run(&self, run: &ExperimentRunHandle) {
+        self.sample_last_epoch(run);
+    }
+
+    fn on_error(&self, run: &ExperimentRunHandle, _failure: &ExperimentFailure) {
+        self.sample_last_epoch(run);
+    }
+
+    fn on_finish(&self, run: &ExperimentRunHandle, _completion: &ExperimentCompletion) {
+        self.epochs.lock().unwrap().remove(&run.run_key());
+    }
+
+    fn on_metric(
+        &self,
+        run: &ExperimentRunHandle,
+        epoch: usize,
+        split: &str,
+        _iteration: usize,
+        _items: &[MetricValue],
+    ) {
+        if split == MEMORY_SPLIT {
+            return;
+        }
+
+        let previous = self.epochs.lock().unwrap().insert(run.run_key(), epoch);
+        if let Some(previous) = previous.filter(|previous| *previous < epoch) {
+            self.sample(run, previous);
+        }
+    }
+}
+
+fn mb_value(name: &str, bytes: u64) -> MetricValue {
+    MetricValue {
+        name: name.to_string(),
+        value: bytes as f64 / (1024.0 * 1024.0),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct HostMemory {
+    rss_kb: u64,
+    peak_rss_kb: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn host_memory() -> Option<HostMemory> {
+    parse_proc_status(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn host_memory() -> Option<HostMemory> {
+    None
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_status(status: &str) -> Option<HostMemory> {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.trim().strip_suffix("kB"))
+            .and_then(|value| value.trim().parse().ok())
+    };
+
+    Some(HostMemory {
+        rss_kb: field("VmRSS:")?,
+        peak_rss_kb: field("VmHWM:")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ExperimentError;
+    use crate::reader::{ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact};
+    use crate::session::{BundleFn, Event, ExperimentSession};
+    use crate::{ArtifactKind, CancelToken, ExperimentId, ExperimentRun, ExperimentRunHandleExt};
+
+    struct MockSession;
+
+    impl ExperimentSession for MockSession {
+        fn record_event(&self, _event: Event) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn save_artifact(
+            &self,
+            _name: &str,
+            _kind: ArtifactKind,
+            _artifact: Box<BundleFn>,
+        ) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn finish(&self, _completion: ExperimentCompletion) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+    }
+
+    struct NoopExperimentDataReader;
+
+    impl ExperimentArtifactReader for NoopExperimentDataReader {
+        fn load_artifact_raw(
+            &self,
+            _experiment_id: ExperimentId,
+            _name: &str,
+        ) -> Result<LoadedArtifact, ExperimentReaderError> {
+            Err(ExperimentReaderError::new("Artifact not found"))
+        }
+    }
+
+    fn create_run(experiment: &str) -> ExperimentRun {
+        ExperimentRun::new(
+            "1",
+            MockSession,
+            NoopExperimentDataReader,
+            CancelToken::default(),
+        )
+        .with_experiment_name(experiment)
+    }
+
+    #[test]
+    fn proc_status_fields_are_parsed_in_kilobytes() {
+        let status =
+            "Name:\ttrain\nVmPeak:\t  900000 kB\nVmHWM:\t  524288 kB\nVmRSS:\t  262144 kB\n";
+
+        assert_eq!(
+            parse_proc_status(status),
+            Some(HostMemory {
+                rss_kb: 262_144,
+                peak_rss_kb: 524_288,
+            })
+        );
+        assert_eq!(parse_proc_status("Name:\ttrain\n"), None);
+    }
+
+    #[test]
+    fn epochs_are_kept_per_experiment_and_dropped_when_the_run_finishes() {
+        let tracker = MemoryTracker::new();
+        let train = create_run("train").handle();
+        let evaluate = create_run("evaluate").handle();
+
+        tracker.on_metric(&train, 3, "train", 0, &[]);
+        tracker.on_metric(&evaluate, 1, "valid", 0, &[]);
+        assert_eq!(
+            tracker.epochs.lock().unwrap().get(&train.run_key()),
+            Some(&3)
+        );
+        assert_eq!(
+            tracker.epochs.lock().unwrap().get(&evaluate.run_key()),
+            Some(&1)
+        );
+
+        tracker.on_finish(&train, &ExperimentCompletion::Cancelled);
+        tracker.on_finish(&evaluate, &ExperimentCompletion::Success);
+        assert!(tracker.epochs.lock().unwrap().is_empty());
+    }
+}
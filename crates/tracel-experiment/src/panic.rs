@@ -0,0 +1,156 @@
+//! Panic capture for experiment jobs.
+//!
+//! A panic inside a job would otherwise only reach the process's stderr, leaving the run marked as
+//! a generic failure. The hook installed here records the panic message, location and backtrace
+//! of the panicking thread so [`crate::ExperimentJob::run`] can attach them to the run before the
+//! panic resumes. Panics are only recorded while the thread runs a job function, see
+//! [`enter_job`], so unrelated panics do not pay for a backtrace capture.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+
+use crate::LogRecord;
+use crate::session::{ExperimentFailure, FailureClass};
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+    static IN_JOB: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as running a job function until dropped, see [`enter_job`].
+pub(crate) struct JobScope {
+    was_in_job: bool,
+}
+
+impl Drop for JobScope {
+    fn drop(&mut self) {
+        IN_JOB.with(|in_job| in_job.set(self.was_in_job));
+    }
+}
+
+/// Record panics of the current thread until the returned scope is dropped.
+///
+/// Any report left over from an earlier panic is discarded, so [`take_report`] only returns
+/// panics raised inside the scope.
+pub(crate) fn enter_job() -> JobScope {
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+    JobScope {
+        was_in_job: IN_JOB.with(|in_job| in_job.replace(true)),
+    }
+}
+
+/// Details of a panic raised inside an experiment job.
+#[derive(Debug, Clone)]
+pub(crate) struct PanicReport {
+    pub(crate) message: String,
+    pub(crate) location: Option<String>,
+    pub(crate) backtrace: Option<String>,
+}
+
+impl PanicReport {
+    /// Build a report from a caught panic payload when the hook did not record one.
+    pub(crate) fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        Self {
+            message: payload_message(payload),
+            location: None,
+            backtrace: None,
+        }
+    }
+
+    /// The failure recorded for the run.
+    pub(crate) fn failure(&self) -> ExperimentFailure {
+        ExperimentFailure::new(FailureClass::of_panic(&self.message), self.failure_reason())
+    }
+
+    fn failure_reason(&self) -> String {
+        match &self.location {
+            Some(location) => format!("panicked at {location}: {}", self.message),
+            None => format!("panicked: {}", self.message),
+        }
+    }
+
+    /// An `error` log record carrying the panic details as attributes.
+    pub(crate) fn to_log_record(&self) -> LogRecord {
+        let mut record = LogRecord::error(self.failure_reason())
+            .with("panic", true)
+            .with(
+                "failure_class",
+                FailureClass::of_panic(&self.message).as_str(),
+            );
+        if let Some(location) = &self.location {
+            record = record.with("location", location.as_str());
+        }
+        if let Some(backtrace) = &self.backtrace {
+            record = record.with("backtrace", backtrace.as_str());
+        }
+        record
+    }
+}
+
+/// Install the process-wide panic hook once, chaining to the previously installed hook.
+pub(crate) fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if IN_JOB.with(Cell::get) {
+                let report = report_from_hook(info);
+                LAST_PANIC.with(|last| *last.borrow_mut() = Some(report));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Take the report recorded for the most recent panic on the current thread.
+pub(crate) fn take_report() -> Option<PanicReport> {
+    LAST_PANIC.with(|last| last.borrow_mut().take())
+}
+
+fn report_from_hook(info: &PanicHookInfo<'_>) -> PanicReport {
+    PanicReport {
+        message: payload_message(info.payload()),
+        location: info.location().map(ToString::to_string),
+        backtrace: Some(Backtrace::force_capture().to_string()),
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::catch_unwind;
+
+    use super::*;
+
+    #[test]
+    fn only_panics_inside_a_job_are_recorded() {
+        install_hook();
+
+        let _ = catch_unwind(|| panic!("outside"));
+        assert!(take_report().is_none());
+
+        let _ = catch_unwind(|| panic!("stale"));
+        let scope = enter_job();
+        assert!(take_report().is_none());
+        let _ = catch_unwind(|| panic!("inside"));
+        drop(scope);
+
+        let report = take_report().unwrap();
+        assert_eq!(report.message, "inside");
+        assert!(report.backtrace.is_some());
+        assert!(!IN_JOB.with(Cell::get));
+    }
+}
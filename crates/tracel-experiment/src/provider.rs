@@ -1,13 +1,30 @@
+use std::any::Any;
 use std::collections::HashMap;
 use std::error::Error;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
 use std::sync::Arc;
+use std::thread::{self, JoinHandle};
 
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 use crate::error::{ExperimentError, ExperimentErrorKind};
+use crate::integration::output::OutputCapture;
 use crate::integration::tracing::try_init_tracing_subscriber;
-use crate::{ExperimentRun, ExperimentRunHandleExt};
+use crate::interrupt;
+use crate::panic::{self, PanicReport};
+use crate::retry::{InputBackoff, OomBackoff};
+use crate::session::{ExperimentCompletion, ExperimentFailure, FailureClass};
+use crate::{
+    CancelToken, ExperimentHook, ExperimentId, ExperimentRun, ExperimentRunHandle,
+    ExperimentRunHandleExt,
+};
+
+/// Attribute naming the group a run belongs to, see [`ExperimentJob::group`].
+const GROUP_ATTRIBUTE: &str = "group";
+/// Attribute holding the parent run's id, see [`ExperimentJob::parent`].
+const PARENT_ATTRIBUTE: &str = "parent_experiment_id";
 
 pub trait ExperimentProvider: Send + Sync + 'static {
     fn create_experiment(
@@ -32,12 +49,22 @@ where
 
 pub struct ExperimentModule {
     provider: Arc<dyn ExperimentProvider>,
+    hooks: Vec<Arc<dyn ExperimentHook>>,
 }
 
 impl ExperimentModule {
     // TODO: Add settings here (e.g., an ExperimentModule builder).
     pub fn new(provider: Arc<dyn ExperimentProvider>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Register a hook applied to every job created by this module afterwards.
+    pub fn with_hook(mut self, hook: impl ExperimentHook) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
     }
 
     pub fn create<I, O>(
@@ -45,7 +72,9 @@ impl ExperimentModule {
         name: &str,
         f: impl ExperimentFn<I, O> + 'static,
     ) -> ExperimentJob<I, O> {
-        ExperimentJob::new(self.provider.clone(), name.to_string(), f)
+        let mut job = ExperimentJob::new(self.provider.clone(), name.to_string(), f);
+        job.hooks = self.hooks.clone();
+        job
     }
 }
 
@@ -53,6 +82,10 @@ pub struct ExperimentJob<I, O> {
     provider: Arc<dyn ExperimentProvider>,
     name: String,
     attributes: HashMap<String, Value>,
+    oom_backoff: Option<InputBackoff<I>>,
+    hooks: Vec<Arc<dyn ExperimentHook>>,
+    cancel_on_interrupt: bool,
+    capture_output: bool,
     f: Arc<dyn ExperimentFn<I, O>>,
 }
 
@@ -62,6 +95,10 @@ impl<I, O> Clone for ExperimentJob<I, O> {
             provider: self.provider.clone(),
             name: self.name.clone(),
             attributes: self.attributes.clone(),
+            oom_backoff: self.oom_backoff.clone(),
+            hooks: self.hooks.clone(),
+            cancel_on_interrupt: self.cancel_on_interrupt,
+            capture_output: self.capture_output,
             f: self.f.clone(),
         }
     }
@@ -76,6 +113,10 @@ impl<I, O> ExperimentJob<I, O> {
             provider,
             name,
             attributes: HashMap::new(),
+            oom_backoff: None,
+            hooks: Vec::new(),
+            cancel_on_interrupt: false,
+            capture_output: false,
             f: Arc::new(f),
         }
     }
@@ -107,25 +148,854 @@ impl<I, O> ExperimentJob<I, O> {
         self
     }
 
+    /// Tag every run of this job as a member of `group`.
+    ///
+    /// Related runs such as sweep members, k-fold splits or backend matrices share a group so they
+    /// can be listed together. The group is recorded as the `group` attribute.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.attributes
+            .insert(GROUP_ATTRIBUTE.to_string(), Value::from(group.into()));
+        self
+    }
+
+    /// Record every run of this job as a child of the experiment `parent`.
+    ///
+    /// The parent id is recorded as the `parent_experiment_id` attribute.
+    pub fn parent(mut self, parent: impl Into<ExperimentId>) -> Self {
+        let parent = parent.into();
+        self.attributes
+            .insert(PARENT_ATTRIBUTE.to_string(), Value::from(parent.as_str()));
+        self
+    }
+
+    /// Register a hook for this job, after any hook inherited from its module.
+    pub fn hook(mut self, hook: impl ExperimentHook) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Retry out-of-memory failures with a halved batch size, as configured by `policy`.
+    ///
+    /// See [`OomBackoff`] for how attempts are recorded.
+    pub fn oom_backoff(mut self, policy: OomBackoff) -> Self
+    where
+        I: Serialize + DeserializeOwned,
+    {
+        self.oom_backoff = Some(InputBackoff::new(policy));
+        self
+    }
+
+    /// Cancel the running experiment on Ctrl-C instead of letting the process die mid-run.
+    ///
+    /// The first Ctrl-C cancels the run's [`CancelToken`]; the job can observe it, save partial
+    /// artifacts and return, and the run is then finalized as cancelled. A second Ctrl-C exits the
+    /// process immediately.
+    pub fn cancel_on_interrupt(mut self) -> Self {
+        self.cancel_on_interrupt = true;
+        self
+    }
+
+    /// Forward this process's stdout and stderr into the run's logs while the job function runs.
+    ///
+    /// The output still reaches the terminal. See [`OutputCapture::capture_process`] for when
+    /// capturing is not possible; the job then runs without it.
+    pub fn capture_output(mut self) -> Self {
+        self.capture_output = true;
+        self
+    }
+
+    /// Create an experiment for this job, run the job function against it and finalize it.
+    ///
+    /// The run is finished on success and failed with the error message otherwise, classified
+    /// with [`FailureClass::of_error`]. If the run was cancelled, it is finalized as cancelled
+    /// whatever the job returned. If the job panics, the panic message, location and
+    /// backtrace are logged to the run and the run is failed before the panic resumes.
+    ///
+    /// With an [`OomBackoff`] policy, out-of-memory failures are retried as new experiments before
+    /// the last failure is returned.
     pub fn run(&self, input: I) -> Result<O, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_with_cancel(input, None)
+    }
+
+    fn run_with_cancel(
+        &self,
+        input: I,
+        cancel: Option<&CancelToken>,
+    ) -> Result<O, Box<dyn std::error::Error + Send + Sync>> {
         let _ = try_init_tracing_subscriber();
+        panic::install_hook();
+
+        let Some(backoff) = &self.oom_backoff else {
+            return self
+                .run_attempt(input, self.attributes.clone(), cancel)
+                .map_err(AttemptFailure::resume);
+        };
+
+        let mut snapshot = backoff.snapshot(&input)?;
+        let mut input = input;
+        let mut attempt = 0;
+        loop {
+            let mut attributes = self.attributes.clone();
+            if attempt > 0 {
+                attributes.insert("oom_retry.attempt".to_string(), Value::from(attempt));
+            }
+            if let Some(batch_size) = backoff.batch_size(&snapshot) {
+                attributes.insert("oom_retry.batch_size".to_string(), Value::from(batch_size));
+            }
+
+            let failure = match self.run_attempt(input, attributes, cancel) {
+                Ok(output) => return Ok(output),
+                Err(failure) => failure,
+            };
+            if failure.class() != Some(FailureClass::OutOfMemory)
+                || attempt >= backoff.max_retries()
+                || cancel.is_some_and(CancelToken::is_cancelled)
+            {
+                return Err(failure.resume());
+            }
 
+            input = match backoff.shrink(&mut snapshot) {
+                Some(input) => input?,
+                None => return Err(failure.resume()),
+            };
+            attempt += 1;
+            tracing::warn!(
+                job = %self.name,
+                attempt,
+                batch_size = backoff.batch_size(&snapshot),
+                "job ran out of memory, retrying with a smaller batch size"
+            );
+        }
+    }
+
+    fn run_attempt(
+        &self,
+        input: I,
+        attributes: HashMap<String, Value>,
+        cancel: Option<&CancelToken>,
+    ) -> Result<O, AttemptFailure> {
         let experiment = self
             .provider
-            .create_experiment(self.name.clone(), self.attributes.clone())?;
+            .create_experiment(self.name.clone(), attributes)
+            .map_err(|err| AttemptFailure::Error(Box::new(err), FailureClass::Infra))?
+            .with_experiment_name(self.name.clone());
+        if let Some(cancel) = cancel {
+            cancel.link(experiment.cancel_token());
+        }
+        let _interrupt = self
+            .cancel_on_interrupt
+            .then(|| interrupt::register(experiment.cancel_token()));
+        experiment.add_hooks(&self.hooks);
         let handle = experiment.handle();
-        let result = handle.in_scope(|| self.f.call(&experiment, input));
+        for hook in &self.hooks {
+            hook.before_run(&handle);
+        }
+        let output_capture = self
+            .capture_output
+            .then(|| OutputCapture::new(handle.clone()).capture_process())
+            .flatten();
+        let job_scope = panic::enter_job();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            handle.in_scope(|| self.f.call(&experiment, input))
+        }));
+        drop(job_scope);
+        drop(output_capture);
+
+        let result = match result {
+            Ok(result) => result,
+            Err(payload) => {
+                let report =
+                    panic::take_report().unwrap_or_else(|| PanicReport::from_payload(&*payload));
+                let failure = report.failure();
+                let _ = handle.log(report.to_log_record());
+                for hook in &self.hooks {
+                    hook.on_error(&handle, &failure);
+                }
+                let class = failure.class;
+                let _ = self.finalize(experiment, &handle, ExperimentCompletion::Failed(failure));
+                return Err(AttemptFailure::Panic(payload, class));
+            }
+        };
+
+        if experiment.cancel_token().is_cancelled() {
+            let _ = self.finalize(experiment, &handle, ExperimentCompletion::Cancelled);
+            return result.map_err(AttemptFailure::Cancelled);
+        }
 
         match result {
             Ok(output) => {
-                experiment.finish()?;
+                for hook in &self.hooks {
+                    hook.after_run(&handle);
+                }
+                self.finalize(experiment, &handle, ExperimentCompletion::Success)
+                    .map_err(|err| AttemptFailure::Error(Box::new(err), FailureClass::Infra))?;
                 Ok(output)
             }
             Err(e) => {
-                let msg = e.to_string();
-                let _ = experiment.fail(msg);
-                Err(e)
+                let class = FailureClass::of_error(&*e);
+                let failure = ExperimentFailure::new(class, e.to_string());
+                for hook in &self.hooks {
+                    hook.on_error(&handle, &failure);
+                }
+                let _ = self.finalize(experiment, &handle, ExperimentCompletion::Failed(failure));
+                Err(AttemptFailure::Error(e, class))
+            }
+        }
+    }
+
+    /// Finalize `experiment` as `completion` and report the recorded completion to the hooks.
+    fn finalize(
+        &self,
+        experiment: ExperimentRun,
+        handle: &ExperimentRunHandle,
+        completion: ExperimentCompletion,
+    ) -> Result<(), ExperimentError> {
+        let result = match completion.clone() {
+            ExperimentCompletion::Success => experiment.finish(),
+            ExperimentCompletion::Failed(failure) => experiment.fail_with(failure),
+            ExperimentCompletion::Cancelled => experiment.finish_cancelled(),
+        };
+        let completion = match (&result, completion) {
+            (Err(err), ExperimentCompletion::Success) => ExperimentCompletion::Failed(
+                ExperimentFailure::new(FailureClass::Infra, err.to_string()),
+            ),
+            (_, completion) => completion,
+        };
+        for hook in &self.hooks {
+            hook.on_finish(handle, &completion);
+        }
+        result
+    }
+}
+
+impl<I, O> ExperimentJob<I, O>
+where
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Run the job on a background thread and return a handle to supervise it.
+    ///
+    /// This is the non-blocking counterpart of [`Self::run`]: the handle can be polled with
+    /// [`ExperimentJobHandle::is_finished`], cancelled with [`ExperimentJobHandle::cancel`], and
+    /// joined with [`ExperimentJobHandle::wait`].
+    pub fn spawn(&self, input: I) -> ExperimentJobHandle<O> {
+        let job = self.clone();
+        let cancel = CancelToken::new();
+        let job_cancel = cancel.clone();
+        let worker = thread::Builder::new()
+            .name(format!("experiment-{}", self.name))
+            .spawn(move || job.run_with_cancel(input, Some(&job_cancel)))
+            .expect("failed to spawn experiment job thread");
+
+        ExperimentJobHandle { worker, cancel }
+    }
+}
+
+/// Handle to an experiment job running on a background thread, returned by
+/// [`ExperimentJob::spawn`].
+pub struct ExperimentJobHandle<O> {
+    worker: JoinHandle<Result<O, Box<dyn Error + Send + Sync>>>,
+    cancel: CancelToken,
+}
+
+impl<O> ExperimentJobHandle<O> {
+    /// Return `true` once the job has returned, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Request cancellation of the job's current experiment run.
+    ///
+    /// Cancellation is cooperative: the job observes it through [`ExperimentRun::cancel_token`].
+    /// No further out-of-memory retries are attempted once the job is cancelled.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Return the token cancelled by [`Self::cancel`], to link further work to the job.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Block until the job returns and hand back its result.
+    ///
+    /// If the job panicked, the panic resumes on the calling thread.
+    pub fn wait(self) -> Result<O, Box<dyn Error + Send + Sync>> {
+        match self.worker.join() {
+            Ok(result) => result,
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+}
+
+/// How a single job attempt failed, kept until the job decides whether to retry.
+enum AttemptFailure {
+    Error(Box<dyn Error + Send + Sync>, FailureClass),
+    Panic(Box<dyn Any + Send>, FailureClass),
+    /// The run was cancelled and the job returned an error while stopping.
+    Cancelled(Box<dyn Error + Send + Sync>),
+}
+
+impl AttemptFailure {
+    fn class(&self) -> Option<FailureClass> {
+        match self {
+            AttemptFailure::Error(_, class) | AttemptFailure::Panic(_, class) => Some(*class),
+            AttemptFailure::Cancelled(_) => None,
+        }
+    }
+
+    /// Return the error to the caller, or resume the panic.
+    fn resume(self) -> Box<dyn Error + Send + Sync> {
+        match self {
+            AttemptFailure::Error(err, _) | AttemptFailure::Cancelled(err) => err,
+            AttemptFailure::Panic(payload, _) => resume_unwind(payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::reader::{ExperimentArtifactReader, ExperimentReaderError, LoadedArtifact};
+    use crate::session::{BundleFn, Event, ExperimentCompletion, ExperimentSession};
+    use crate::{ArtifactKind, CancelToken, ExperimentId};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockSession {
+        events: Mutex<Vec<Event>>,
+        completion: Mutex<Option<ExperimentCompletion>>,
+    }
+
+    impl ExperimentSession for MockSession {
+        fn record_event(&self, event: Event) -> Result<(), ExperimentError> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn save_artifact(
+            &self,
+            _name: &str,
+            _kind: ArtifactKind,
+            _artifact: Box<BundleFn>,
+        ) -> Result<(), ExperimentError> {
+            Ok(())
+        }
+
+        fn finish(&self, completion: ExperimentCompletion) -> Result<(), ExperimentError> {
+            *self.completion.lock().unwrap() = Some(completion);
+            Ok(())
+        }
+    }
+
+    struct NoopExperimentDataReader;
+
+    impl ExperimentArtifactReader for NoopExperimentDataReader {
+        fn load_artifact_raw(
+            &self,
+            _experiment_id: ExperimentId,
+            _name: &str,
+        ) -> Result<LoadedArtifact, ExperimentReaderError> {
+            Err(ExperimentReaderError::new("Artifact not found"))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockProvider {
+        session: Arc<MockSession>,
+        attributes: Mutex<Vec<HashMap<String, Value>>>,
+    }
+
+    impl ExperimentProvider for MockProvider {
+        fn create_experiment(
+            &self,
+            name: String,
+            attributes: HashMap<String, Value>,
+        ) -> Result<ExperimentRun, ExperimentError> {
+            self.attributes.lock().unwrap().push(attributes);
+            Ok(ExperimentRun::new(
+                name,
+                self.session.clone(),
+                NoopExperimentDataReader,
+                CancelToken::default(),
+            ))
+        }
+    }
+
+    #[test]
+    fn run_fails_the_experiment_with_the_job_error() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let job = ExperimentModule::new(provider).create(
+            "failing",
+            |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                Err("bad config".into())
+            },
+        );
+
+        assert!(job.run(()).is_err());
+        assert_eq!(
+            *session.completion.lock().unwrap(),
+            Some(ExperimentCompletion::Failed(ExperimentFailure::new(
+                FailureClass::User,
+                "bad config"
+            )))
+        );
+    }
+
+    #[test]
+    fn run_classifies_allocation_failures_as_out_of_memory() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let job = ExperimentModule::new(provider).create(
+            "oom",
+            |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                Err("CUDA error: out of memory".into())
+            },
+        );
+
+        assert!(job.run(()).is_err());
+        match session.completion.lock().unwrap().as_ref() {
+            Some(ExperimentCompletion::Failed(failure)) => {
+                assert_eq!(failure.class, FailureClass::OutOfMemory);
+            }
+            completion => panic!("unexpected completion: {completion:?}"),
+        }
+    }
+
+    #[test]
+    fn run_classifies_backend_errors_as_infra() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let job = ExperimentModule::new(provider).create(
+            "infra",
+            |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                Err(ExperimentError::new(ExperimentErrorKind::Artifact, "upload failed").into())
+            },
+        );
+
+        assert!(job.run(()).is_err());
+        match session.completion.lock().unwrap().as_ref() {
+            Some(ExperimentCompletion::Failed(failure)) => {
+                assert_eq!(failure.class, FailureClass::Infra);
+            }
+            completion => panic!("unexpected completion: {completion:?}"),
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingHook {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ExperimentHook for RecordingHook {
+        fn before_run(&self, _run: &crate::ExperimentRunHandle) {
+            self.calls.lock().unwrap().push("before_run".to_string());
+        }
+
+        fn after_run(&self, _run: &crate::ExperimentRunHandle) {
+            self.calls.lock().unwrap().push("after_run".to_string());
+        }
+
+        fn on_error(&self, _run: &crate::ExperimentRunHandle, failure: &ExperimentFailure) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("on_error({})", failure.reason));
+        }
+
+        fn on_finish(&self, _run: &crate::ExperimentRunHandle, completion: &ExperimentCompletion) {
+            let completion = match completion {
+                ExperimentCompletion::Success => "success".to_string(),
+                ExperimentCompletion::Failed(failure) => format!("failed({})", failure.class),
+                ExperimentCompletion::Cancelled => "cancelled".to_string(),
+            };
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("on_finish({completion})"));
+        }
+
+        fn on_metric(
+            &self,
+            _run: &crate::ExperimentRunHandle,
+            epoch: usize,
+            split: &str,
+            _iteration: usize,
+            items: &[crate::MetricValue],
+        ) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("on_metric({epoch}, {split}, {})", items.len()));
+        }
+
+        fn on_anomaly(&self, _run: &crate::ExperimentRunHandle, anomaly: &crate::MetricAnomaly) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("on_anomaly({})", anomaly.metric));
+        }
+    }
+
+    #[test]
+    fn hooks_observe_a_successful_run() {
+        let hook = RecordingHook::default();
+        let calls = hook.calls.clone();
+        let job = ExperimentModule::new(Arc::new(MockProvider::default()))
+            .with_hook(hook)
+            .create(
+                "hooked",
+                |run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                    run.log_metric(
+                        1,
+                        "train",
+                        10,
+                        vec![crate::MetricValue {
+                            name: "loss".to_string(),
+                            value: 0.5,
+                        }],
+                    )?;
+                    Ok(())
+                },
+            );
+
+        job.run(()).unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "before_run",
+                "on_metric(1, train, 1)",
+                "after_run",
+                "on_finish(success)"
+            ]
+        );
+    }
+
+    #[test]
+    fn hooks_observe_a_failed_run() {
+        let hook = RecordingHook::default();
+        let calls = hook.calls.clone();
+        let job = ExperimentModule::new(Arc::new(MockProvider::default()))
+            .create(
+                "hooked",
+                |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                    Err("boom".into())
+                },
+            )
+            .hook(hook);
+
+        assert!(job.run(()).is_err());
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["before_run", "on_error(boom)", "on_finish(failed(user))"]
+        );
+    }
+
+    #[test]
+    fn anomaly_detector_notifies_hooks_and_cancels_the_run() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let hook = RecordingHook::default();
+        let calls = hook.calls.clone();
+        let job = ExperimentModule::new(provider)
+            .with_hook(crate::AnomalyDetector::new().cancel_run())
+            .with_hook(hook)
+            .create(
+                "diverging",
+                |run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                    for value in [0.9, f64::NAN, f64::NAN] {
+                        if run.cancel_token().is_cancelled() {
+                            break;
+                        }
+                        let loss = crate::MetricValue {
+                            name: "loss".to_string(),
+                            value,
+                        };
+                        run.log_metric(1, "train", 1, vec![loss])?;
+                    }
+                    Ok(())
+                },
+            );
+
+        job.run(()).unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "before_run",
+                "on_metric(1, train, 1)",
+                "on_anomaly(loss)",
+                "on_metric(1, train, 1)",
+                "on_finish(cancelled)",
+            ]
+        );
+        assert_eq!(
+            *session.completion.lock().unwrap(),
+            Some(ExperimentCompletion::Cancelled)
+        );
+        assert!(session.events.lock().unwrap().iter().any(|event| matches!(
+            event,
+            Event::Log(record) if record.attributes.get("anomaly") == Some(&Value::from("not_finite"))
+        )));
+    }
+
+    #[test]
+    fn memory_tracker_samples_each_finished_epoch() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let job = ExperimentModule::new(provider)
+            .create(
+                "tracked",
+                |run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                    for epoch in [1, 1, 2] {
+                        let loss = crate::MetricValue {
+                            name: "loss".to_string(),
+                            value: 0.5,
+                        };
+                        run.log_metric(epoch, "train", 1, vec![loss])?;
+                    }
+                    Ok(())
+                },
+            )
+            .hook(crate::MemoryTracker::new().backend_memory(|| Some(512 * 1024 * 1024)));
+
+        job.run(()).unwrap();
+
+        let samples: Vec<_> = session
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Metrics {
+                    epoch,
+                    split,
+                    items,
+                    ..
+                } if split == crate::MEMORY_SPLIT => {
+                    let backend = items.iter().find(|item| item.name == "backend_memory_mb")?;
+                    Some((*epoch, backend.value))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(samples, vec![(1, 512.0), (2, 512.0)]);
+    }
+
+    #[test]
+    fn group_and_parent_are_recorded_as_attributes() {
+        let provider = Arc::new(MockProvider::default());
+        let job = ExperimentModule::new(provider.clone())
+            .create(
+                "member",
+                |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                    Ok(())
+                },
+            )
+            .group("lr-sweep")
+            .parent(7);
+
+        job.run(()).unwrap();
+
+        let attributes = provider.attributes.lock().unwrap();
+        assert_eq!(attributes[0]["group"], "lr-sweep");
+        assert_eq!(attributes[0]["parent_experiment_id"], "7");
+    }
+
+    #[test]
+    fn cancelled_run_is_finalized_as_cancelled() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        // `cancel_on_interrupt` is left out: it installs a process-wide Ctrl-C handler, and the
+        // handler cancels runs through the same token.
+        let job = ExperimentModule::new(provider).create(
+            "interrupted",
+            |run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                // Stand-in for a Ctrl-C delivered while the job is running.
+                run.cancel_token().cancel();
+                Err("stopped early".into())
+            },
+        );
+
+        assert_eq!(job.run(()).unwrap_err().to_string(), "stopped early");
+        assert_eq!(
+            *session.completion.lock().unwrap(),
+            Some(ExperimentCompletion::Cancelled)
+        );
+    }
+
+    #[test]
+    fn spawn_runs_the_job_in_the_background() {
+        let job = ExperimentModule::new(Arc::new(MockProvider::default())).create(
+            "background",
+            |_run: &ExperimentRun, input: u32| -> Result<u32, Box<dyn Error + Send + Sync>> {
+                Ok(input * 2)
+            },
+        );
+
+        let handle = job.spawn(21);
+
+        assert_eq!(handle.wait().unwrap(), 42);
+    }
+
+    #[test]
+    fn spawned_job_observes_cancellation() {
+        let job = ExperimentModule::new(Arc::new(MockProvider::default())).create(
+            "cancellable",
+            |run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                let token = run.cancel_token();
+                while !token.is_cancelled() {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                Err("cancelled".into())
+            },
+        );
+
+        let handle = job.spawn(());
+        assert!(!handle.is_finished());
+        handle.cancel();
+
+        assert_eq!(handle.wait().unwrap_err().to_string(), "cancelled");
+    }
+
+    #[derive(Serialize, serde::Deserialize)]
+    struct TrainingConfig {
+        training: BatchConfig,
+    }
+
+    #[derive(Serialize, serde::Deserialize)]
+    struct BatchConfig {
+        batch_size: u64,
+    }
+
+    #[test]
+    fn oom_backoff_retries_with_halved_batch_size_until_it_fits() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_job = seen.clone();
+        let job = ExperimentModule::new(provider)
+            .create(
+                "sweep",
+                move |_run: &ExperimentRun,
+                      config: TrainingConfig|
+                      -> Result<u64, Box<dyn Error + Send + Sync>> {
+                    let batch_size = config.training.batch_size;
+                    seen_by_job.lock().unwrap().push(batch_size);
+                    if batch_size > 8 {
+                        return Err("wgpu: out of memory".into());
+                    }
+                    Ok(batch_size)
+                },
+            )
+            .oom_backoff(OomBackoff::new("training.batch_size").max_retries(3));
+
+        let output = job
+            .run(TrainingConfig {
+                training: BatchConfig { batch_size: 32 },
+            })
+            .unwrap();
+
+        assert_eq!(output, 8);
+        assert_eq!(*seen.lock().unwrap(), vec![32, 16, 8]);
+        assert_eq!(
+            *session.completion.lock().unwrap(),
+            Some(ExperimentCompletion::Success)
+        );
+    }
+
+    #[test]
+    fn oom_backoff_gives_up_after_max_retries() {
+        let provider = Arc::new(MockProvider::default());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_job = seen.clone();
+        let job = ExperimentModule::new(provider)
+            .create(
+                "sweep",
+                move |_run: &ExperimentRun,
+                      config: TrainingConfig|
+                      -> Result<(), Box<dyn Error + Send + Sync>> {
+                    seen_by_job.lock().unwrap().push(config.training.batch_size);
+                    Err("out of memory".into())
+                },
+            )
+            .oom_backoff(OomBackoff::new("training.batch_size").max_retries(1));
+
+        let result = job.run(TrainingConfig {
+            training: BatchConfig { batch_size: 32 },
+        });
+
+        assert!(result.is_err());
+        assert_eq!(*seen.lock().unwrap(), vec![32, 16]);
+    }
+
+    #[test]
+    fn oom_backoff_does_not_retry_other_failures() {
+        let provider = Arc::new(MockProvider::default());
+        let seen = Arc::new(Mutex::new(0));
+        let seen_by_job = seen.clone();
+        let job = ExperimentModule::new(provider)
+            .create(
+                "sweep",
+                move |_run: &ExperimentRun,
+                      _config: TrainingConfig|
+                      -> Result<(), Box<dyn Error + Send + Sync>> {
+                    *seen_by_job.lock().unwrap() += 1;
+                    Err("invalid learning rate".into())
+                },
+            )
+            .oom_backoff(OomBackoff::new("training.batch_size"));
+
+        let result = job.run(TrainingConfig {
+            training: BatchConfig { batch_size: 32 },
+        });
+
+        assert!(result.is_err());
+        assert_eq!(*seen.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn run_reports_panics_with_backtrace_before_resuming() {
+        let provider = Arc::new(MockProvider::default());
+        let session = provider.session.clone();
+        let job = ExperimentModule::new(provider).create(
+            "panicking",
+            |_run: &ExperimentRun, _input: ()| -> Result<(), Box<dyn Error + Send + Sync>> {
+                panic!("loss diverged");
+            },
+        );
+
+        let result = catch_unwind(AssertUnwindSafe(|| job.run(())));
+        assert!(result.is_err());
+
+        match session.completion.lock().unwrap().as_ref() {
+            Some(ExperimentCompletion::Failed(failure)) => {
+                assert_eq!(failure.class, FailureClass::Panic);
+                assert!(failure.reason.starts_with("panicked at "));
+                assert!(failure.reason.ends_with("loss diverged"));
             }
+            completion => panic!("unexpected completion: {completion:?}"),
         }
+
+        let events = session.events.lock().unwrap();
+        let record = events
+            .iter()
+            .find_map(|event| match event {
+                Event::Log(record) => Some(record),
+                _ => None,
+            })
+            .expect("the panic should have been logged");
+        assert_eq!(record.level, crate::LogLevel::Error);
+        assert_eq!(
+            record.attributes.get("panic").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+        assert!(record.attributes.contains_key("backtrace"));
+        assert!(record.attributes.contains_key("location"));
     }
 }
@@ -0,0 +1,138 @@
+//! Automatic retries of out-of-memory job failures with a smaller batch size.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{ExperimentError, ExperimentErrorKind};
+
+/// Opt-in policy that retries a job with a halved batch size when it runs out of memory.
+///
+/// When a job run fails with [`crate::session::FailureClass::OutOfMemory`], the integer field at
+/// [`Self::new`]'s path in the job input is halved and the job is run again as a new experiment,
+/// up to [`Self::max_retries`] times. Each attempt is recorded as its own experiment carrying
+/// `oom_retry.attempt` and `oom_retry.batch_size` attributes, so the history stays visible.
+///
+/// Enable it with [`crate::ExperimentJob::oom_backoff`]:
+///
+/// ```ignore
+/// let job = module
+///     .create("train", train)
+///     .oom_backoff(OomBackoff::new("training.batch_size").max_retries(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OomBackoff {
+    field: String,
+    max_retries: usize,
+}
+
+impl OomBackoff {
+    /// Retry by halving the integer field at `field`, a `.`-separated path into the job input.
+    ///
+    /// Retries up to 3 times by default.
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            max_retries: 3,
+        }
+    }
+
+    /// Maximum number of retries after the first attempt.
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// The `.`-separated path of the batch size field.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+}
+
+/// An [`OomBackoff`] bound to a job input type, able to rebuild the input with a smaller batch.
+pub(crate) struct InputBackoff<I> {
+    policy: OomBackoff,
+    snapshot: fn(&I) -> Result<Value, serde_json::Error>,
+    restore: fn(Value) -> Result<I, serde_json::Error>,
+}
+
+impl<I> Clone for InputBackoff<I> {
+    fn clone(&self) -> Self {
+        Self {
+            policy: self.policy.clone(),
+            snapshot: self.snapshot,
+            restore: self.restore,
+        }
+    }
+}
+
+impl<I> InputBackoff<I>
+where
+    I: Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(policy: OomBackoff) -> Self {
+        Self {
+            policy,
+            snapshot: |input| serde_json::to_value(input),
+            restore: serde_json::from_value,
+        }
+    }
+}
+
+impl<I> InputBackoff<I> {
+    pub(crate) fn max_retries(&self) -> usize {
+        self.policy.max_retries
+    }
+
+    /// Serialize the job input so it can be rebuilt for later attempts.
+    pub(crate) fn snapshot(&self, input: &I) -> Result<Value, ExperimentError> {
+        (self.snapshot)(input).map_err(|err| {
+            ExperimentError::with_source(
+                ExperimentErrorKind::Internal,
+                "Failed to serialize job input for out-of-memory retries",
+                err,
+            )
+        })
+    }
+
+    /// The current batch size recorded in `snapshot`, if the field holds an integer.
+    pub(crate) fn batch_size(&self, snapshot: &Value) -> Option<u64> {
+        self.field_path(snapshot).and_then(Value::as_u64)
+    }
+
+    /// Halve the batch size in `snapshot` and rebuild the input from it.
+    ///
+    /// Returns `None` when the field is missing, not an integer, or already `1`.
+    pub(crate) fn shrink(&self, snapshot: &mut Value) -> Option<Result<I, ExperimentError>> {
+        let batch_size = self.batch_size(snapshot)?;
+        if batch_size <= 1 {
+            return None;
+        }
+
+        let field = self.field_path_mut(snapshot)?;
+        *field = Value::from(batch_size / 2);
+
+        Some((self.restore)(snapshot.clone()).map_err(|err| {
+            ExperimentError::with_source(
+                ExperimentErrorKind::Internal,
+                "Failed to rebuild job input with a smaller batch size",
+                err,
+            )
+        }))
+    }
+
+    fn field_path<'a>(&self, snapshot: &'a Value) -> Option<&'a Value> {
+        self.policy
+            .field
+            .split('.')
+            .try_fold(snapshot, |value, key| value.get(key))
+    }
+
+    fn field_path_mut<'a>(&self, snapshot: &'a mut Value) -> Option<&'a mut Value> {
+        self.policy
+            .field
+            .split('.')
+            .try_fold(snapshot, |value, key| value.get_mut(key))
+    }
+}
@@ -3,8 +3,11 @@ use std::sync::Arc;
 use tracel_artifact::bundle::FsBundle;
 
 use crate::{
-    ArtifactKind, ExperimentId, MetricSpec, MetricValue, activity::ActivityEvent,
-    error::ExperimentError, log::LogRecord, reader::ArtifactRef,
+    ArtifactKind, ExperimentId, MetricSpec, MetricValue,
+    activity::ActivityEvent,
+    error::{ExperimentError, ExperimentErrorKind},
+    log::LogRecord,
+    reader::ArtifactRef,
 };
 
 #[derive(Debug, Clone)]
@@ -40,13 +43,133 @@ pub enum ExperimentCompletion {
     /// The run completed successfully.
     Success,
 
-    /// The run failed with the provided reason.
-    Failed(String),
+    /// The run failed; the [`ExperimentFailure`] carries its class and reason.
+    Failed(ExperimentFailure),
 
     /// The run was cancelled before completion.
     Cancelled,
 }
 
+/// Broad category of an experiment failure.
+///
+/// Dashboards and retry policies use the class to react differently to, say, an out-of-memory
+/// error (worth retrying with a smaller configuration) and a bug in user code (not worth retrying).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureClass {
+    /// The job function returned an error.
+    User,
+
+    /// The job function panicked.
+    Panic,
+
+    /// The backend ran out of device or host memory.
+    OutOfMemory,
+
+    /// The job was cancelled before it completed.
+    Cancelled,
+
+    /// The job exceeded the time limit set by its runner.
+    Timeout,
+
+    /// The job's project failed to build, so the job function never ran.
+    Build,
+
+    /// The experiment backend or its transport failed, independently of the job itself.
+    Infra,
+}
+
+impl FailureClass {
+    /// The lowercase name of the class.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailureClass::User => "user",
+            FailureClass::Panic => "panic",
+            FailureClass::OutOfMemory => "out_of_memory",
+            FailureClass::Cancelled => "cancelled",
+            FailureClass::Timeout => "timeout",
+            FailureClass::Build => "build",
+            FailureClass::Infra => "infra",
+        }
+    }
+
+    /// Classify an error returned by a job function.
+    ///
+    /// Experiment errors raised by the backend are classified as [`FailureClass::Infra`] and
+    /// cancellation errors as [`FailureClass::Cancelled`]. Other errors whose message reports an
+    /// allocation failure are classified as [`FailureClass::OutOfMemory`], and anything else as
+    /// [`FailureClass::User`].
+    pub fn of_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(error) = error.downcast_ref::<ExperimentError>() {
+            match error.kind {
+                ExperimentErrorKind::Internal
+                | ExperimentErrorKind::Artifact
+                | ExperimentErrorKind::AlreadyFinished
+                | ExperimentErrorKind::InactiveRun => return FailureClass::Infra,
+                ExperimentErrorKind::Cancelled => return FailureClass::Cancelled,
+            }
+        }
+
+        if is_out_of_memory(&error.to_string()) {
+            FailureClass::OutOfMemory
+        } else {
+            FailureClass::User
+        }
+    }
+
+    /// Classify a panic from its message.
+    pub fn of_panic(message: &str) -> Self {
+        if is_out_of_memory(message) {
+            FailureClass::OutOfMemory
+        } else {
+            FailureClass::Panic
+        }
+    }
+}
+
+impl std::fmt::Display for FailureClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether `message` reports an allocation failure from a Burn backend or the allocator.
+pub(crate) fn is_out_of_memory(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "out of memory",
+        "outofmemory",
+        "out_of_memory",
+        "memory allocation of",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// A classified failure reason recorded for an experiment run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExperimentFailure {
+    /// Broad category of the failure.
+    pub class: FailureClass,
+
+    /// Human-readable failure reason.
+    pub reason: String,
+}
+
+impl ExperimentFailure {
+    pub fn new(class: FailureClass, reason: impl Into<String>) -> Self {
+        Self {
+            class,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ExperimentFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.class, self.reason)
+    }
+}
+
 pub type BundleFn<'a> = dyn FnOnce(&mut FsBundle) -> Result<(), ExperimentError> + 'a;
 
 /// Session-level implementation for the active experiment run.
@@ -82,3 +205,33 @@ where
         self.as_ref().finish(completion)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_are_classified_by_kind_then_message() {
+        let classify = |error: ExperimentError| FailureClass::of_error(&error);
+
+        assert_eq!(
+            classify(ExperimentError::new(
+                ExperimentErrorKind::Cancelled,
+                "out of memory"
+            )),
+            FailureClass::Cancelled
+        );
+        assert_eq!(
+            classify(ExperimentError::new(
+                ExperimentErrorKind::Artifact,
+                "upload failed"
+            )),
+            FailureClass::Infra
+        );
+
+        let user = std::io::Error::other("CUDA out of memory");
+        assert_eq!(FailureClass::of_error(&user), FailureClass::OutOfMemory);
+        let user = std::io::Error::other("bad config");
+        assert_eq!(FailureClass::of_error(&user), FailureClass::User);
+    }
+}
@@ -0,0 +1,171 @@
+//! Managed per-run directories for job outputs and temporary files.
+//!
+//! Jobs often need somewhere on disk for intermediate files, or for outputs written by external
+//! tools. [`crate::ExperimentRun::scratch_dir`] and [`crate::ExperimentRun::artifact_dir`] hand out
+//! directories under a temporary root owned by the run, so job code does not hardcode paths such
+//! as `/tmp/guide`. When the run is finalized, every non-empty artifact directory is saved as an
+//! artifact, and the whole root is removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tempfile::TempDir;
+use tracel_artifact::bundle::{BundleSink, FsBundle};
+
+use crate::ArtifactKind;
+use crate::error::{ExperimentError, ExperimentErrorKind};
+use crate::session::ExperimentSession;
+
+pub(crate) struct RunDirs {
+    root: Mutex<Option<TempDir>>,
+    artifacts: Mutex<Vec<ArtifactDir>>,
+}
+
+struct ArtifactDir {
+    name: String,
+    kind: ArtifactKind,
+    path: PathBuf,
+}
+
+impl RunDirs {
+    pub(crate) fn new() -> Self {
+        Self {
+            root: Mutex::new(None),
+            artifacts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The `scratch` directory of the run, created on first use.
+    pub(crate) fn scratch(&self) -> Result<PathBuf, ExperimentError> {
+        let path = self.root()?.join("scratch");
+        create_dir(&path)?;
+        Ok(path)
+    }
+
+    /// The directory saved as artifact `name` when the run is finalized, created on first use.
+    pub(crate) fn artifact(
+        &self,
+        name: &str,
+        kind: ArtifactKind,
+    ) -> Result<PathBuf, ExperimentError> {
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(ExperimentError::new(
+                ExperimentErrorKind::Artifact,
+                format!("Invalid artifact directory name: {name:?}"),
+            ));
+        }
+
+        let path = self.root()?.join("artifacts").join(name);
+        create_dir(&path)?;
+
+        let mut artifacts = self.artifacts.lock().unwrap();
+        if !artifacts.iter().any(|dir| dir.name == name) {
+            artifacts.push(ArtifactDir {
+                name: name.to_string(),
+                kind,
+                path: path.clone(),
+            });
+        }
+        Ok(path)
+    }
+
+    /// Save every non-empty artifact directory through `session`, then remove the run's root.
+    ///
+    /// Failures are logged rather than returned, so they never prevent the run from completing.
+    pub(crate) fn finalize(&self, session: &dyn ExperimentSession) {
+        let artifacts = std::mem::take(&mut *self.artifacts.lock().unwrap());
+        for dir in artifacts {
+            let files = match collect_files(&dir.path, &dir.path) {
+                Ok(files) => files,
+                Err(err) => {
+                    tracing::warn!(
+                        artifact = %dir.name,
+                        "Failed to read artifact directory: {err}"
+                    );
+                    continue;
+                }
+            };
+            if files.is_empty() {
+                continue;
+            }
+
+            let copy = |bundle: &mut FsBundle| copy_files(bundle, &dir.path, &files);
+            if let Err(err) = session.save_artifact(&dir.name, dir.kind, Box::new(copy)) {
+                tracing::warn!(
+                    artifact = %dir.name,
+                    "Failed to save artifact directory: {err}"
+                );
+            }
+        }
+
+        self.root.lock().unwrap().take();
+    }
+
+    fn root(&self) -> Result<PathBuf, ExperimentError> {
+        let mut root = self.root.lock().unwrap();
+        if let Some(root) = root.as_ref() {
+            return Ok(root.path().to_path_buf());
+        }
+
+        let dir = TempDir::with_prefix("tracel-run-").map_err(|err| {
+            ExperimentError::with_source(
+                ExperimentErrorKind::Internal,
+                "Failed to create run directory",
+                err,
+            )
+        })?;
+        let path = dir.path().to_path_buf();
+        *root = Some(dir);
+        Ok(path)
+    }
+}
+
+fn create_dir(path: &Path) -> Result<(), ExperimentError> {
+    fs::create_dir_all(path).map_err(|err| {
+        ExperimentError::with_source(
+            ExperimentErrorKind::Internal,
+            format!("Failed to create run directory {}", path.display()),
+            err,
+        )
+    })
+}
+
+/// Relative paths of every file under `current`, using `/` separators.
+fn collect_files(root: &Path, current: &Path) -> Result<Vec<String>, std::io::Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(current)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(collect_files(root, &path)?);
+            continue;
+        }
+
+        let rel = path.strip_prefix(root).map_err(std::io::Error::other)?;
+        let rel: Vec<_> = rel
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        files.push(rel.join("/"));
+    }
+    Ok(files)
+}
+
+fn copy_files(bundle: &mut FsBundle, root: &Path, files: &[String]) -> Result<(), ExperimentError> {
+    for rel in files {
+        let mut file = fs::File::open(root.join(rel)).map_err(|err| {
+            ExperimentError::with_source(
+                ExperimentErrorKind::Artifact,
+                format!("Failed to open artifact file {rel}"),
+                err,
+            )
+        })?;
+        bundle.put_file(rel, &mut file).map_err(|err| {
+            ExperimentError::new(
+                ExperimentErrorKind::Artifact,
+                format!("Failed to copy artifact file {rel}: {err}"),
+            )
+        })?;
+    }
+    Ok(())
+}
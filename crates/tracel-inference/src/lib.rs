@@ -11,6 +11,7 @@ mod session;
 mod stream;
 
 pub mod integration;
+pub mod profile;
 pub mod sink;
 
 pub use context::SessionGuard;
@@ -18,6 +19,7 @@ pub use error::InferenceError;
 pub use inference::{Inference, IntoInference, inference_fn};
 pub use input::InferenceInput;
 pub use output::{InferenceOutput, OutputWriter, OutputWriterError};
+pub use profile::{LatencyReport, ProfileSettings, StageLatency};
 pub use provider::{InferenceJob, InferenceModule, InferenceProvider};
 pub use session::{InferenceId, InferenceSession};
 pub use stream::InferenceStream;
@@ -0,0 +1,320 @@
+//! Latency profiling for inference jobs.
+//!
+//! [`InferenceJob::profile`](crate::InferenceJob::profile) runs an inference for a number of
+//! warmup iterations, then times each stage of a request over the measured iterations:
+//!
+//! - `decode`: producing the typed input, e.g. deserializing a request body.
+//! - `forward`: the inference call itself, excluding the time spent emitting outputs.
+//! - `emit`: handing each output to the caller, e.g. serializing a response.
+//!
+//! Profiling requests are not telemetry: they run under a single session created before timing
+//! starts, whose metrics and logs are discarded.
+//!
+//! The resulting [`LatencyReport`] holds percentiles per stage. It prints as a table and
+//! serializes to JSON, so serving latency can be checked against an SLA before deployment.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::InferenceError;
+use crate::output::{OutputWriter, OutputWriterError};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// How many iterations [`InferenceJob::profile`](crate::InferenceJob::profile) runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileSettings {
+    /// Iterations run before measuring, to let caches and lazy initialization settle.
+    pub warmup: usize,
+    /// Iterations measured for the report. Must be at least one.
+    pub iterations: usize,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            warmup: 3,
+            iterations: 20,
+        }
+    }
+}
+
+/// Latency distribution of one request stage, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StageLatency {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl StageLatency {
+    fn from_samples(samples: &mut [Duration]) -> Self {
+        samples.sort_unstable();
+        let total: Duration = samples.iter().sum();
+        let mean = match samples.len() {
+            0 => Duration::ZERO,
+            len => total / len as u32,
+        };
+
+        Self {
+            mean_ms: as_ms(mean),
+            p50_ms: as_ms(percentile(samples, 50.0)),
+            p95_ms: as_ms(percentile(samples, 95.0)),
+            p99_ms: as_ms(percentile(samples, 99.0)),
+            max_ms: as_ms(samples.last().copied().unwrap_or_default()),
+        }
+    }
+}
+
+/// Per-stage latency of an inference, measured by
+/// [`InferenceJob::profile`](crate::InferenceJob::profile).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyReport {
+    pub warmup: usize,
+    pub iterations: usize,
+    pub decode: StageLatency,
+    pub forward: StageLatency,
+    pub emit: StageLatency,
+    /// The whole request: decode, forward and emit.
+    pub total: StageLatency,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} iterations after {} warmup",
+            self.iterations, self.warmup
+        )?;
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>10} {:>10} {:>10}",
+            "stage", "p50 ms", "p95 ms", "p99 ms", "mean ms"
+        )?;
+        for (name, stage) in [
+            ("decode", &self.decode),
+            ("forward", &self.forward),
+            ("emit", &self.emit),
+            ("total", &self.total),
+        ] {
+            writeln!(
+                f,
+                "{name:<8} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+                stage.p50_ms, stage.p95_ms, stage.p99_ms, stage.mean_ms
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Drive the profiling loop. `run` executes one request against the given writer and returns how
+/// long the inference call took.
+pub(crate) fn profile<I, O, D, E, R>(
+    settings: &ProfileSettings,
+    mut decode: D,
+    emit: E,
+    mut run: R,
+) -> Result<LatencyReport, InferenceError>
+where
+    O: 'static,
+    D: FnMut() -> Result<I, BoxError>,
+    E: Fn(O) -> Result<(), BoxError> + Send + Sync + 'static,
+    R: FnMut(I, TimedWriter<O>) -> Duration,
+{
+    if settings.iterations == 0 {
+        return Err(InferenceError::new(
+            "Profiling needs at least one measured iteration",
+        ));
+    }
+
+    let emit: Arc<dyn Fn(O) -> Result<(), BoxError> + Send + Sync> = Arc::new(emit);
+    let mut decode_samples = Vec::with_capacity(settings.iterations);
+    let mut forward_samples = Vec::with_capacity(settings.iterations);
+    let mut emit_samples = Vec::with_capacity(settings.iterations);
+    let mut total_samples = Vec::with_capacity(settings.iterations);
+
+    for iteration in 0..settings.warmup + settings.iterations {
+        let start = Instant::now();
+        let input =
+            decode().map_err(|e| InferenceError::with_source("Failed to decode input", e))?;
+        let decoded = start.elapsed();
+
+        let state = Arc::new(Mutex::new(WriterState::default()));
+        let writer = TimedWriter {
+            emit: emit.clone(),
+            state: state.clone(),
+        };
+        let ran = run(input, writer);
+
+        let mut state = state.lock().unwrap();
+        if let Some(error) = state.error.take() {
+            return Err(InferenceError::with_source(
+                "Inference failed while profiling",
+                error,
+            ));
+        }
+        if iteration < settings.warmup {
+            continue;
+        }
+
+        decode_samples.push(decoded);
+        forward_samples.push(ran.saturating_sub(state.emitting));
+        emit_samples.push(state.emitting);
+        total_samples.push(decoded + ran);
+    }
+
+    Ok(LatencyReport {
+        warmup: settings.warmup,
+        iterations: settings.iterations,
+        decode: StageLatency::from_samples(&mut decode_samples),
+        forward: StageLatency::from_samples(&mut forward_samples),
+        emit: StageLatency::from_samples(&mut emit_samples),
+        total: StageLatency::from_samples(&mut total_samples),
+    })
+}
+
+#[derive(Default)]
+struct WriterState {
+    emitting: Duration,
+    error: Option<BoxError>,
+}
+
+/// Output writer that times the caller's emit function and keeps the first error.
+pub(crate) struct TimedWriter<O> {
+    emit: Arc<dyn Fn(O) -> Result<(), BoxError> + Send + Sync>,
+    state: Arc<Mutex<WriterState>>,
+}
+
+impl<O> OutputWriter<O> for TimedWriter<O> {
+    fn write(&self, output: O) -> Result<(), OutputWriterError> {
+        let start = Instant::now();
+        let result = (self.emit)(output);
+        let mut state = self.state.lock().unwrap();
+        state.emitting += start.elapsed();
+        result.map_err(|error| {
+            let message = error.to_string();
+            state.error.get_or_insert(error);
+            OutputWriterError::Unknown(message.into())
+        })
+    }
+
+    fn error(&self, error: BoxError) -> Result<(), OutputWriterError> {
+        self.state.lock().unwrap().error.get_or_insert(error);
+        Ok(())
+    }
+
+    fn finish(&self, _duration: Duration) {}
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: impl IntoIterator<Item = u64>) -> Vec<Duration> {
+        values.into_iter().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut samples = ms((1..=100).rev());
+
+        let latency = StageLatency::from_samples(&mut samples);
+
+        assert_eq!(latency.p50_ms, 50.0);
+        assert_eq!(latency.p95_ms, 95.0);
+        assert_eq!(latency.p99_ms, 99.0);
+        assert_eq!(latency.max_ms, 100.0);
+        assert_eq!(latency.mean_ms, 50.5);
+    }
+
+    #[test]
+    fn warmup_iterations_are_not_measured() {
+        let settings = ProfileSettings {
+            warmup: 2,
+            iterations: 5,
+        };
+        let mut decoded = 0;
+        let emitted = Arc::new(Mutex::new(0));
+        let counter = emitted.clone();
+
+        let report = profile(
+            &settings,
+            || {
+                decoded += 1;
+                Ok(decoded)
+            },
+            move |_output: i32| {
+                *counter.lock().unwrap() += 1;
+                Ok(())
+            },
+            |input, writer| {
+                writer.write(input).unwrap();
+                writer.write(input).unwrap();
+                Duration::ZERO
+            },
+        )
+        .unwrap();
+
+        assert_eq!(decoded, 7);
+        assert_eq!(*emitted.lock().unwrap(), 14);
+        assert_eq!(report.warmup, 2);
+        assert_eq!(report.iterations, 5);
+    }
+
+    #[test]
+    fn inference_errors_fail_the_profile() {
+        let result = profile(
+            &ProfileSettings::default(),
+            || Ok(1),
+            |_output: i32| Ok(()),
+            |_input, writer| {
+                writer.error("model exploded".into()).unwrap();
+                Duration::ZERO
+            },
+        );
+
+        let err = result.unwrap_err();
+        assert_eq!(err.message, "Inference failed while profiling");
+        assert_eq!(err.source.unwrap().to_string(), "model exploded");
+    }
+
+    #[test]
+    fn zero_iterations_are_rejected() {
+        let settings = ProfileSettings {
+            warmup: 1,
+            iterations: 0,
+        };
+        let mut ran = false;
+
+        let result = profile(
+            &settings,
+            || Ok(1),
+            |_output: i32| Ok(()),
+            |_input, _writer| {
+                ran = true;
+                Duration::ZERO
+            },
+        );
+
+        assert!(result.is_err());
+        assert!(!ran);
+    }
+}
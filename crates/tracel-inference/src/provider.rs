@@ -3,7 +3,9 @@ use std::sync::Arc;
 use crate::OutputWriter;
 use crate::error::InferenceError;
 use crate::inference::{Inference, IntoInference};
+use crate::profile::{self, LatencyReport, ProfileSettings};
 use crate::session::InferenceSession;
+use crate::sink::NoopSink;
 use crate::stream::InferenceStream;
 
 /// Backend port that creates per-request [`InferenceSession`]s.
@@ -120,6 +122,28 @@ where
     pub fn stream_once(&self, input: I) -> Result<InferenceStream<O>, InferenceError> {
         self.stream(std::iter::once(input))
     }
+
+    /// Measure per-stage request latency over repeated single-input runs.
+    ///
+    /// Each iteration calls `decode` for a fresh input, runs the inference inline, and passes every
+    /// output to `emit`. All iterations share one session that is not opened from the provider,
+    /// so profiling requests are not recorded. See [`crate::profile`] for how stages are timed.
+    /// The first error from `decode`, the inference or `emit` stops profiling.
+    pub fn profile<D, E>(
+        &self,
+        settings: &ProfileSettings,
+        decode: D,
+        emit: E,
+    ) -> Result<LatencyReport, InferenceError>
+    where
+        D: FnMut() -> Result<I, Box<dyn std::error::Error + Send + Sync>>,
+        E: Fn(O) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let session = InferenceSession::new(format!("{}-profile", self.name), Arc::new(NoopSink));
+        profile::profile(settings, decode, emit, |input, writer| {
+            session.run_timed(self.inference.as_ref(), std::iter::once(input), writer)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -150,6 +174,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn profile_does_not_open_provider_sessions() {
+        // `TestProvider` panics if a session is requested.
+        let job = InferenceModule::new(Arc::new(TestProvider)).create("echo", Echo);
+        let settings = ProfileSettings {
+            warmup: 1,
+            iterations: 3,
+        };
+
+        let report = job.profile(&settings, || Ok(1), |_output| Ok(())).unwrap();
+
+        assert_eq!(report.iterations, 3);
+    }
+
     #[test]
     fn create_accepts_both_impls_and_closures() {
         let module = InferenceModule::new(Arc::new(TestProvider));
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serde_json::Value;
 
@@ -192,6 +193,17 @@ impl InferenceSession {
     /// without an [`InferenceProvider`](crate::InferenceProvider) or
     /// [`InferenceJob`](crate::InferenceJob).
     pub fn run<Inf, It, W>(&self, inference: &Inf, input: It, output: W)
+    where
+        Inf: Inference + ?Sized,
+        It: IntoIterator<Item = Inf::Input>,
+        It::IntoIter: Send + 'static,
+        W: OutputWriter<Inf::Output> + 'static,
+    {
+        self.run_timed(inference, input, output);
+    }
+
+    /// Like [`run`](Self::run), returning how long [`Inference::infer`] itself took.
+    pub(crate) fn run_timed<Inf, It, W>(&self, inference: &Inf, input: It, output: W) -> Duration
     where
         Inf: Inference + ?Sized,
         It: IntoIterator<Item = Inf::Input>,
@@ -204,7 +216,9 @@ impl InferenceSession {
             InferenceOutput::from_writer(output).with_observer(Arc::new(SessionStatsObserver {
                 session: self.clone(),
             }));
+        let start = Instant::now();
         inference.infer(self, input, output);
+        start.elapsed()
     }
 }
 